@@ -4,14 +4,16 @@ mod resources;
 mod struct_enum;
 mod types;
 
-use aide::openapi;
+use aide::openapi::{self, ReferenceOr};
+use anyhow::{Context as _, bail};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use crate::IncludeMode;
+use crate::{IncludeMode, ResourceGrouping, config::CustomFormats};
 
 pub(crate) use self::{
-    resources::{Resource, Resources},
-    types::Types,
+    resources::{BodyContentType, Resource, Resources},
+    types::{FieldType, Types},
 };
 
 #[derive(Default, Deserialize, Serialize)]
@@ -26,22 +28,20 @@ impl Api {
         paths: openapi::Paths,
         components: &mut openapi::Components,
         webhooks: &[String],
-        include_mode: IncludeMode,
-        excluded_operations: &BTreeSet<String>,
-        specified_operations: &BTreeSet<String>,
+        filter: &OperationFilter,
+        resource_grouping: ResourceGrouping,
+        custom_formats: &CustomFormats,
+        untagged_enums: bool,
     ) -> anyhow::Result<Self> {
-        let resources = resources::from_openapi(
-            paths,
-            &components.schemas,
-            include_mode,
-            excluded_operations,
-            specified_operations,
-        )?;
+        let resources =
+            resources::from_openapi(paths, components, filter, resource_grouping, custom_formats)?;
         let types = types::from_referenced_components(
             &resources,
             &mut components.schemas,
             webhooks,
-            include_mode,
+            filter.include_mode,
+            custom_formats,
+            untagged_enums,
         );
 
         Ok(Self { resources, types })
@@ -59,6 +59,54 @@ impl FromIterator<Api> for Api {
     }
 }
 
+/// Which operations to generate code for.
+pub(crate) struct OperationFilter {
+    pub include_mode: IncludeMode,
+    pub excluded_operations: BTreeSet<String>,
+    pub specified_operations: BTreeSet<String>,
+    /// The API version to include, or `None` to include all of them.
+    ///
+    /// Operations without a version are always included.
+    pub api_version: Option<String>,
+    /// Whether to include operations with request or success response bodies that aren't JSON.
+    pub non_json_bodies: bool,
+    /// Whether to include operations whose success responses have different bodies.
+    pub distinct_success_bodies: bool,
+}
+
+impl OperationFilter {
+    /// Whether to include an operation.
+    ///
+    /// Operations without an operationId can't be specified or excluded, but are included
+    /// depending on `x-hidden` (they are skipped later if the resource grouping needs the ID).
+    fn includes(&self, op: &openapi::Operation) -> bool {
+        let op_id = op.operation_id.as_deref();
+
+        // verbose, but very easy to understand
+        let x_hidden = op.extensions.get("x-hidden").is_some_and(|val| val == true);
+        let include_operation = match self.include_mode {
+            IncludeMode::OnlyPublic => !x_hidden,
+            IncludeMode::PublicAndHidden => true,
+            IncludeMode::OnlyHidden => x_hidden,
+            IncludeMode::OnlySpecified => {
+                op_id.is_some_and(|id| self.specified_operations.contains(id))
+            }
+        };
+        include_operation && op_id.is_none_or(|id| !self.excluded_operations.contains(id))
+    }
+
+    fn accepts_content_type(&self, content_type: BodyContentType) -> bool {
+        self.non_json_bodies || content_type == BodyContentType::Json
+    }
+
+    fn includes_version(&self, version: Option<&str>) -> bool {
+        match (&self.api_version, version) {
+            (Some(api_version), Some(version)) => api_version == version,
+            _ => true,
+        }
+    }
+}
+
 pub(crate) fn get_schema_name(maybe_ref: Option<&str>) -> Option<String> {
     let r = maybe_ref?;
     let schema_name = r.strip_prefix("#/components/schemas/");
@@ -71,6 +119,34 @@ pub(crate) fn get_schema_name(maybe_ref: Option<&str>) -> Option<String> {
     Some(schema_name?.to_owned())
 }
 
+/// Resolve a possibly `$ref`erenced object against the matching `components` map.
+///
+/// `prefix` is the path of that map, e.g. `#/components/parameters/`. References to other
+/// references are followed, up to a fixed depth.
+pub(crate) fn resolve_ref<T: Clone>(
+    mut item: ReferenceOr<T>,
+    components: &IndexMap<String, ReferenceOr<T>>,
+    prefix: &str,
+) -> anyhow::Result<T> {
+    const MAX_DEPTH: usize = 8;
+
+    for _ in 0..MAX_DEPTH {
+        let reference = match item {
+            ReferenceOr::Item(t) => return Ok(t),
+            ReferenceOr::Reference { reference, .. } => reference,
+        };
+        let name = reference.strip_prefix(prefix).with_context(|| {
+            format!("unsupported $ref `{reference}`, expected `{prefix}` prefix")
+        })?;
+        item = components
+            .get(name)
+            .with_context(|| format!("$ref `{reference}` not found"))?
+            .clone();
+    }
+
+    bail!("too many levels of $ref indirection")
+}
+
 pub(crate) mod toplevel_resources_serde {
     use std::fmt;
 
@@ -79,7 +155,7 @@ pub(crate) mod toplevel_resources_serde {
         ser::{SerializeSeq as _, Serializer},
     };
 
-    use super::{Resource, Resources};
+    use super::{Resource, Resources, resources::toplevel_resource_key};
 
     pub(crate) fn serialize<S>(map: &Resources, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -111,7 +187,8 @@ pub(crate) mod toplevel_resources_serde {
             {
                 let mut resources = Resources::new();
                 while let Some(r) = seq.next_element::<Resource>()? {
-                    resources.insert(r.name.clone(), r);
+                    let key = toplevel_resource_key(r.version.as_deref(), &r.name);
+                    resources.insert(key, r);
                 }
                 Ok(resources)
             }
@@ -1,16 +1,20 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem,
+};
 
 use aide::openapi::{self, ReferenceOr};
-use anyhow::{Context as _, bail};
+use anyhow::{Context as _, bail, ensure};
+use heck::{ToSnakeCase as _, ToUpperCamelCase as _};
 use indexmap::IndexMap;
-use schemars::schema::{InstanceType, Schema};
+use schemars::schema::{InstanceType, Schema, SchemaObject};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    get_schema_name,
-    types::{FieldType, serialize_field_type},
+    OperationFilter, get_schema_name, resolve_ref,
+    types::{FieldType, Validation, serialize_field_type},
 };
-use crate::IncludeMode;
+use crate::{ResourceGrouping, config::CustomFormats};
 
 /// The API operations of the API client we generate.
 ///
@@ -19,34 +23,59 @@ pub(crate) type Resources = BTreeMap<String, Resource>;
 
 pub(crate) fn from_openapi(
     paths: openapi::Paths,
-    component_schemas: &IndexMap<String, openapi::SchemaObject>,
-    include_mode: IncludeMode,
-    excluded_operations: &BTreeSet<String>,
-    specified_operations: &BTreeSet<String>,
+    components: &mut openapi::Components,
+    filter: &OperationFilter,
+    resource_grouping: ResourceGrouping,
+    custom_formats: &CustomFormats,
 ) -> anyhow::Result<Resources> {
     let mut resources = BTreeMap::new();
 
     for (path, pi) in paths {
-        let path_item = pi
+        let mut path_item = pi
             .into_item()
             .context("$ref paths are currently not supported")?;
+        let path_item_params = mem::take(&mut path_item.parameters);
+
+        for (method, mut op) in path_item {
+            op.parameters = match merge_parameters(&path_item_params, op.parameters, components) {
+                Ok(p) => p,
+                Err(e) => {
+                    tracing::warn!(path, method, "unsupported parameter: {e:#}");
+                    continue;
+                }
+            };
 
-        if !path_item.parameters.is_empty() {
-            tracing::info!("parameters at the path item level are not currently supported");
-            continue;
-        }
+            if !filter.includes(&op) {
+                continue;
+            }
 
-        for (method, op) in path_item {
             if let Some((res_path, op)) = Operation::from_openapi(
                 &path,
                 method,
                 op,
-                component_schemas,
-                include_mode,
-                excluded_operations,
-                specified_operations,
+                components,
+                filter,
+                resource_grouping,
+                custom_formats,
             ) {
-                let resource = get_or_insert_resource(&mut resources, res_path);
+                // Without per-version subdirectories, resources of the same name would be written
+                // to the same file, so they're merged into one
+                let resource_version = op
+                    .version
+                    .as_deref()
+                    .filter(|_| filter.api_version.is_none());
+                let resource = get_or_insert_resource(&mut resources, resource_version, res_path);
+                if resource.operations.iter().any(|o| o.name == op.name) {
+                    tracing::warn!(
+                        path,
+                        method,
+                        "skipping operation `{}`, resource `{}` already has an operation of \
+                         that name",
+                        op.name,
+                        resource.name,
+                    );
+                    continue;
+                }
                 resource.operations.push(op);
             }
         }
@@ -59,12 +88,28 @@ pub(crate) fn referenced_components(resources: &Resources) -> impl Iterator<Item
     resources.values().flat_map(Resource::referenced_components)
 }
 
-fn get_or_insert_resource(resources: &mut Resources, path: Vec<String>) -> &mut Resource {
+/// Key of a top-level resource in [`Resources`].
+///
+/// Includes the version, so resources of the same name but different API versions don't clash
+/// when generating code for all versions.
+pub(crate) fn toplevel_resource_key(version: Option<&str>, name: &str) -> String {
+    match version {
+        Some(version) => format!("{version}.{name}"),
+        None => name.to_owned(),
+    }
+}
+
+fn get_or_insert_resource<'a>(
+    resources: &'a mut Resources,
+    version: Option<&str>,
+    path: Vec<String>,
+) -> &'a mut Resource {
     let mut path_iter = path.into_iter();
     let mut name = path_iter.next().expect("path must be non-empty");
+    let version = version.map(ToOwned::to_owned);
     let mut r = resources
-        .entry(name.clone())
-        .or_insert_with(|| Resource::new(name.clone()));
+        .entry(toplevel_resource_key(version.as_deref(), &name))
+        .or_insert_with(|| Resource::new(name.clone(), version.clone()));
 
     for sub_name in path_iter {
         name.push('.');
@@ -73,7 +118,7 @@ fn get_or_insert_resource(resources: &mut Resources, path: Vec<String>) -> &mut
         r = r
             .subresources
             .entry(sub_name)
-            .or_insert_with(|| Resource::new(name.clone()));
+            .or_insert_with(|| Resource::new(name.clone(), version.clone()));
     }
 
     r
@@ -83,14 +128,20 @@ fn get_or_insert_resource(resources: &mut Resources, path: Vec<String>) -> &mut
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Resource {
     pub name: String,
+    /// The API version of this resource's operations, e.g. `v1`.
+    ///
+    /// Only set when generating code for all versions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
     pub operations: Vec<Operation>,
     pub subresources: Resources,
 }
 
 impl Resource {
-    fn new(name: String) -> Self {
+    fn new(name: String, version: Option<String>) -> Self {
         Self {
             name,
+            version,
             operations: Vec::new(),
             subresources: BTreeMap::new(),
         }
@@ -109,16 +160,39 @@ impl Resource {
                     res.insert(name);
                 }
             }
+            for param in &operation.header_params {
+                if let FieldType::SchemaRef { name } = &param.r#type {
+                    res.insert(name);
+                }
+            }
             if let Some(name) = &operation.request_body_schema_name {
                 res.insert(name);
             }
-            if let Some(name) = &operation.response_body_schema_name {
+            for name in operation
+                .success_responses
+                .iter()
+                .filter_map(|r| r.schema_name.as_deref())
+            {
+                res.insert(name);
+            }
+            for (_, name) in &operation.error_responses {
                 res.insert(name);
             }
         }
 
         res
     }
+
+    /// Imports that need to be in scope for the parameter type names of this resource's
+    /// operations in the given language.
+    ///
+    /// Subresources are rendered separately, so their operations are not included.
+    pub(crate) fn required_imports(&self, lang: &str) -> BTreeSet<&'static str> {
+        self.operations
+            .iter()
+            .flat_map(|operation| operation.required_imports(lang))
+            .collect()
+    }
 }
 
 /// A named HTTP endpoint.
@@ -128,6 +202,9 @@ pub(crate) struct Operation {
     id: String,
     /// The name to use for the operation in code.
     pub(crate) name: String,
+    /// The API version this operation belongs to, e.g. `v1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) version: Option<String>,
     /// Description of the operation to use for documentation.
     #[serde(skip_serializing_if = "Option::is_none")]
     description: Option<String>,
@@ -144,21 +221,127 @@ pub(crate) struct Operation {
     /// Only required string-typed parameters are currently supported.
     path_params: Vec<String>,
     /// Header parameters.
-    ///
-    /// Only string-typed parameters are currently supported.
     header_params: Vec<HeaderParam>,
     /// Query parameters.
     query_params: Vec<QueryParam>,
+    /// Media type of the request body, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_body_content_type: Option<BodyContentType>,
     /// Name of the request body type, if any.
+    ///
+    /// Only set for JSON and multipart form data bodies.
     #[serde(skip_serializing_if = "Option::is_none")]
     request_body_schema_name: Option<String>,
     /// Some request bodies are required, but all the fields are optional (i.e. the CLI can omit
     /// this from the argument list).
     /// Only useful when `request_body_schema_name` is `Some`.
     request_body_all_optional: bool,
+    /// Media type of the response body, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_body_content_type: Option<BodyContentType>,
     /// Name of the response body type, if any.
+    ///
+    /// Only set for JSON bodies.
+    ///
+    /// This and `response_body_content_type` are only set if all success responses with a body
+    /// have the same one, otherwise templates have to look at `success_responses`. Such operations
+    /// are only included with `--distinct-success-bodies`.
     #[serde(skip_serializing_if = "Option::is_none")]
     response_body_schema_name: Option<String>,
+    /// Success responses, by status code.
+    success_responses: Vec<SuccessResponse>,
+    /// Error responses with a JSON body, as status code and name of the body type.
+    ///
+    /// The status code is either a number, a range like `4XX`, or `default`.
+    error_responses: Vec<(String, String)>,
+}
+
+/// A success (2xx) response of an [`Operation`].
+#[derive(Deserialize, Serialize)]
+struct SuccessResponse {
+    /// The status code, e.g. `200`.
+    status: u16,
+    /// Media type of the response body, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<BodyContentType>,
+    /// Name of the response body type, if any.
+    ///
+    /// Only set for JSON bodies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_name: Option<String>,
+}
+
+/// Supported media types of request and response bodies.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum BodyContentType {
+    /// `application/json`
+    Json,
+    /// `multipart/form-data`, usually with file uploads, described by an object schema.
+    MultipartFormData,
+    /// `application/octet-stream`, i.e. raw bytes.
+    OctetStream,
+    /// `text/plain`
+    TextPlain,
+}
+
+impl BodyContentType {
+    /// All supported media types, in order of preference.
+    const ALL: [Self; 4] = [
+        Self::Json,
+        Self::MultipartFormData,
+        Self::OctetStream,
+        Self::TextPlain,
+    ];
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MultipartFormData => "multipart/form-data",
+            Self::OctetStream => "application/octet-stream",
+            Self::TextPlain => "text/plain",
+        }
+    }
+
+    /// Whether the body is described by a named type.
+    fn has_schema(self) -> bool {
+        matches!(self, Self::Json | Self::MultipartFormData)
+    }
+
+    /// Pick the media type to generate code for out of a body's `content`.
+    ///
+    /// Media type parameters like `; charset=utf-8` are ignored.
+    fn select(
+        content: IndexMap<String, openapi::MediaType>,
+    ) -> anyhow::Result<(Self, openapi::MediaType)> {
+        let mut content: IndexMap<_, _> = content
+            .into_iter()
+            .map(|(mime, media_type)| {
+                let essence = mime.split(';').next().unwrap_or_default().trim();
+                (essence.to_ascii_lowercase(), media_type)
+            })
+            .collect();
+
+        for content_type in Self::ALL {
+            if let Some(media_type) = content.swap_remove(content_type.mime_type()) {
+                return Ok((content_type, media_type));
+            }
+        }
+
+        // JSON-based media types, e.g. `application/hal+json` or `application/problem+json`
+        if let Some(idx) = content
+            .keys()
+            .position(|mime| mime.starts_with("application/") && mime.ends_with("+json"))
+            && let Some((_, media_type)) = content.swap_remove_index(idx)
+        {
+            return Ok((Self::Json, media_type));
+        }
+
+        bail!(
+            "unsupported content type(s) {:?}",
+            content.keys().collect::<Vec<_>>()
+        )
+    }
 }
 
 impl Operation {
@@ -171,63 +354,66 @@ impl Operation {
         path: &str,
         method: &str,
         op: openapi::Operation,
-        component_schemas: &IndexMap<String, aide::openapi::SchemaObject>,
-        include_mode: IncludeMode,
-        excluded_operations: &BTreeSet<String>,
-        specified_operations: &BTreeSet<String>,
+        components: &mut openapi::Components,
+        filter: &OperationFilter,
+        resource_grouping: ResourceGrouping,
+        custom_formats: &CustomFormats,
     ) -> Option<(Vec<String>, Self)> {
-        let Some(op_id) = op.operation_id else {
-            // ignore operations without an operationId
-            return None;
-        };
-        tracing::Span::current().record("op_id", &op_id);
-
-        // verbose, but very easy to understand
-        let x_hidden = op.extensions.get("x-hidden").is_some_and(|val| val == true);
-        let include_operation = match include_mode {
-            IncludeMode::OnlyPublic => !x_hidden,
-            IncludeMode::PublicAndHidden => true,
-            IncludeMode::OnlyHidden => x_hidden,
-            IncludeMode::OnlySpecified => specified_operations.contains(&op_id),
-        };
-        if !include_operation || excluded_operations.contains(&op_id) {
-            return None;
+        if let Some(op_id) = &op.operation_id {
+            tracing::Span::current().record("op_id", op_id);
         }
 
-        let mut op_id_parts_iter = op_id.split('.');
-        let version = op_id_parts_iter
-            .next()
-            .expect("split iter always contains at least one item");
-        let Some(op_name) = op_id_parts_iter.next_back() else {
-            tracing::debug!("skipping operation whose ID doesn't contain a period");
-            return None;
-        };
-
-        let res_path: Vec<_> = op_id_parts_iter.map(ToOwned::to_owned).collect();
-        if res_path.is_empty() {
-            tracing::debug!("skipping operation whose ID only contains one period");
+        let (version, res_path, op_name) =
+            resource_path_and_name(resource_grouping, method, path, &op)?;
+        if !filter.includes_version(version.as_deref()) {
+            tracing::debug!(version, "skipping operation of a different API version");
             return None;
         }
 
-        if version != "v1" {
-            tracing::warn!("found operation whose ID does not begin with v1");
-            return None;
-        }
+        // Operations without an operationId get one in the same format as the ones in Svix's spec
+        let op_id = op.operation_id.clone().unwrap_or_else(|| {
+            let mut parts: Vec<_> = version
+                .iter()
+                .chain(&res_path)
+                .map(String::as_str)
+                .collect();
+            parts.push(&op_name);
+            parts.join(".")
+        });
+
+        // Prefix for the names of types synthesized from inline body schemas. When generating code
+        // for all versions, it includes the version so the same operation of different API
+        // versions doesn't clash.
+        let inline_type_prefix = format!(
+            "{}{}{}",
+            version
+                .as_deref()
+                .filter(|_| filter.api_version.is_none())
+                .unwrap_or_default()
+                .to_upper_camel_case(),
+            res_path.join(".").to_upper_camel_case(),
+            op_name.to_upper_camel_case()
+        );
 
         let mut path_params = Vec::new();
         let mut query_params = Vec::new();
         let mut header_params = Vec::new();
 
         for param in op.parameters {
-            match param {
-                ReferenceOr::Reference { .. } => {
-                    tracing::warn!("$ref parameters are not currently supported");
+            let param = match resolve_ref(param, &components.parameters, "#/components/parameters/")
+            {
+                Ok(p) => p,
+                Err(e) => {
+                    tracing::warn!("unsupported parameter: {e:#}");
                     return None;
                 }
-                ReferenceOr::Item(openapi::Parameter::Path {
+            };
+
+            match param {
+                openapi::Parameter::Path {
                     parameter_data,
                     style: openapi::PathStyle::Simple,
-                }) => {
+                } => {
                     assert!(parameter_data.required, "no optional path params");
                     if let Err(e) = enforce_string_parameter(&parameter_data) {
                         tracing::warn!("unsupported path parameter: {e}");
@@ -236,30 +422,35 @@ impl Operation {
 
                     path_params.push(parameter_data.name);
                 }
-                ReferenceOr::Item(openapi::Parameter::Header {
+                openapi::Parameter::Header {
                     parameter_data,
                     style: openapi::HeaderStyle::Simple,
-                }) => {
-                    if parameter_data.name != "idempotency-key" {
-                        tracing::warn!(name = parameter_data.name, "unknown header parameter");
-                    }
-
-                    if let Err(e) = enforce_string_parameter(&parameter_data) {
-                        tracing::warn!("unsupported header parameter: {e}");
-                        return None;
-                    }
+                } => {
+                    let name = parameter_data.name;
+                    let _guard = tracing::info_span!("field_type_from_openapi", name).entered();
+                    let r#type =
+                        match FieldType::from_openapi(parameter_data.format, custom_formats) {
+                            Ok(t) => t,
+                            Err(e) => {
+                                tracing::warn!("unsupported header parameter type: {e}");
+                                return None;
+                            }
+                        };
 
                     header_params.push(HeaderParam {
-                        name: parameter_data.name,
+                        name,
+                        description: parameter_data.description,
                         required: parameter_data.required,
+                        deprecated: parameter_data.deprecated.unwrap_or_default(),
+                        r#type,
                     });
                 }
-                ReferenceOr::Item(openapi::Parameter::Query {
+                openapi::Parameter::Query {
                     parameter_data,
                     allow_reserved: false,
                     style: openapi::QueryStyle::Form,
                     allow_empty_value: None,
-                }) => {
+                } => {
                     let name = parameter_data.name;
                     if method == "post" && name == "get_if_exists" {
                         tracing::debug!("ignoring get_if_exists query parameter");
@@ -267,22 +458,25 @@ impl Operation {
                     }
 
                     let _guard = tracing::info_span!("field_type_from_openapi", name).entered();
-                    let r#type = match FieldType::from_openapi(parameter_data.format) {
-                        Ok(t) => t,
-                        Err(e) => {
-                            tracing::warn!("unsupported query parameter type: {e}");
-                            return None;
-                        }
-                    };
+                    let validation = Validation::from_parameter_format(&parameter_data.format);
+                    let r#type =
+                        match FieldType::from_openapi(parameter_data.format, custom_formats) {
+                            Ok(t) => t,
+                            Err(e) => {
+                                tracing::warn!("unsupported query parameter type: {e}");
+                                return None;
+                            }
+                        };
 
                     query_params.push(QueryParam {
                         name,
                         description: parameter_data.description,
                         required: parameter_data.required,
                         r#type,
+                        validation,
                     });
                 }
-                ReferenceOr::Item(parameter) => {
+                parameter => {
                     tracing::warn!(
                         ?parameter,
                         "this kind of parameter is not currently supported"
@@ -292,115 +486,216 @@ impl Operation {
             }
         }
 
-        let request_body_all_optional = op
+        let request_body = match op
             .request_body
-            .as_ref()
-            .map(|r| {
-                match r {
-                    ReferenceOr::Reference { .. } => {
-                        unimplemented!("reference")
-                    }
-                    ReferenceOr::Item(body) => {
-                        if let Some(mt) = body.content.get("application/json") {
-                            match mt.schema.as_ref().map(|so| &so.json_schema) {
-                                Some(Schema::Object(schemars::schema::SchemaObject {
-                                    object: Some(ov),
-                                    ..
-                                })) => {
-                                    return ov.required.is_empty();
-                                }
-                                Some(Schema::Object(schemars::schema::SchemaObject {
-                                    reference: Some(s),
-                                    ..
-                                })) => {
-                                    match component_schemas
-                                        .get(
-                                            &get_schema_name(Some(s)).expect("schema should exist"),
-                                        )
-                                        .map(|so| &so.json_schema)
-                                    {
-                                        Some(Schema::Object(schemars::schema::SchemaObject {
-                                            object: Some(ov),
-                                            ..
-                                        })) => {
-                                            return ov.required.is_empty();
-                                        }
-                                        _ => unimplemented!("double ref not supported"),
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-                false
-            })
-            .unwrap_or_default();
-
-        let request_body_schema_name = op.request_body.and_then(|b| match b {
-            ReferenceOr::Item(mut req_body) => {
-                assert!(req_body.required);
-                assert!(req_body.extensions.is_empty());
-                assert_eq!(req_body.content.len(), 1);
-                let json_body = req_body
-                    .content
-                    .swap_remove("application/json")
-                    .expect("should have JSON body");
-                assert!(json_body.extensions.is_empty());
-                match json_body.schema.expect("no json body schema?!").json_schema {
-                    Schema::Bool(_) => {
-                        tracing::error!("unexpected bool schema");
-                        None
-                    }
-                    Schema::Object(obj) => {
-                        if !obj.is_ref() {
-                            tracing::error!(?obj, "unexpected non-$ref json body schema");
-                        }
+            .map(|b| resolve_ref(b, &components.request_bodies, "#/components/requestBodies/"))
+            .transpose()
+        {
+            Ok(b) => b,
+            Err(e) => {
+                tracing::warn!("unsupported request body: {e:#}");
+                return None;
+            }
+        };
+
+        // `required` defaults to `false`, but an optional body can simply be treated like one whose
+        // fields are all optional
+        let request_body_required = request_body.as_ref().is_none_or(|b| b.required);
+
+        let (request_body_content_type, request_body) = match request_body
+            .map(|req_body| BodyContentType::select(req_body.content))
+            .transpose()
+        {
+            Ok(b) => b.unzip(),
+            Err(e) => {
+                tracing::warn!("unsupported request body: {e:#}");
+                return None;
+            }
+        };
+        if let Some(content_type) = request_body_content_type
+            && !filter.accepts_content_type(content_type)
+        {
+            tracing::warn!(
+                "skipping operation with {} request body",
+                content_type.mime_type()
+            );
+            return None;
+        }
+
+        let request_body_all_optional = !request_body_required
+            || request_body.as_ref().is_some_and(|mt| {
+                let schema = match mt.schema.as_ref().map(|so| &so.json_schema) {
+                    Some(Schema::Object(obj)) if obj.is_ref() => {
                         get_schema_name(obj.reference.as_deref())
+                            .and_then(|name| components.schemas.get(&name))
+                            .map(|so| &so.json_schema)
+                    }
+                    schema => schema,
+                };
+                // Only direct object schemas are checked, anything else (e.g. `allOf` or `oneOf`)
+                // is assumed to have required fields
+                matches!(
+                    schema,
+                    Some(Schema::Object(SchemaObject { object: Some(ov), .. }))
+                        if ov.required.is_empty()
+                )
+            });
+
+        let request_body_schema_name = match request_body
+            .filter(|_| request_body_content_type.is_some_and(BodyContentType::has_schema))
+        {
+            Some(body) => {
+                let Some(schema) = body.schema else {
+                    tracing::warn!("skipping operation whose request body has no schema");
+                    return None;
+                };
+                match body_schema_name(
+                    schema.json_schema,
+                    format!("{inline_type_prefix}Request"),
+                    &mut components.schemas,
+                ) {
+                    Ok(name) => Some(name),
+                    Err(e) => {
+                        tracing::warn!("skipping operation with unsupported request body: {e:#}");
+                        return None;
                     }
                 }
             }
-            ReferenceOr::Reference { .. } => {
-                tracing::error!("$ref request bodies are not currently supported");
-                None
-            }
-        });
+            None => None,
+        };
 
-        let response_body_schema_name = op.responses.and_then(|r| {
-            assert_eq!(r.default, None);
-            assert!(r.extensions.is_empty());
-            let mut success_responses = r.responses.into_iter().filter(|(st, _)| {
+        let mut success_responses = Vec::new();
+        let mut error_responses = Vec::new();
+        let has_responses = op.responses.is_some();
+        if let Some(r) = op.responses {
+            for (st, resp) in r.responses {
                 match st {
                     openapi::StatusCode::Code(c) => match c {
                         0..100 => tracing::error!("invalid status code < 100"),
                         100..200 => tracing::error!("what is this? status code {c}..."),
-                        200..300 => return true,
+                        200..300 => success_responses.push((c, resp)),
                         300..400 => tracing::error!("what is this? status code {c}..."),
-                        400.. => {}
+                        400.. => error_responses.push((st.to_string(), resp)),
                     },
+                    openapi::StatusCode::Range(4 | 5) => {
+                        error_responses.push((st.to_string(), resp));
+                    }
                     openapi::StatusCode::Range(_) => {
                         tracing::error!("unsupported status code range");
                     }
                 }
+            }
+            if let Some(resp) = r.default {
+                error_responses.push(("default".to_owned(), resp));
+            }
+        }
 
-                false
-            });
+        if has_responses && success_responses.is_empty() {
+            tracing::warn!("skipping operation without success response");
+            return None;
+        }
 
-            let (_, resp) = success_responses
-                .next()
-                .expect("every operation must have one success response");
-            let schema_name = response_body_schema_name(resp);
-            for (_, resp) in success_responses {
-                assert_eq!(schema_name, response_body_schema_name(resp));
+        // The inline body type of the first success response is named without the status code, as
+        // are identical inline bodies of other success responses
+        let mut first_body_schema = None;
+        let success_responses = success_responses
+            .into_iter()
+            .map(|(status, resp)| {
+                let (content_type, schema) = match response_body(resp, components) {
+                    Ok(b) => b,
+                    Err(e) => {
+                        tracing::warn!(
+                            status,
+                            "skipping operation with unsupported response: {e:#}"
+                        );
+                        return None;
+                    }
+                };
+                if let Some(content_type) = content_type
+                    && !filter.accepts_content_type(content_type)
+                {
+                    tracing::warn!(
+                        status,
+                        "skipping operation with {} response body",
+                        content_type.mime_type()
+                    );
+                    return None;
+                }
+                let schema_name = match schema {
+                    Some(schema) => {
+                        let first_body_schema =
+                            first_body_schema.get_or_insert_with(|| schema.clone());
+                        let inline_type_name = if *first_body_schema == schema {
+                            format!("{inline_type_prefix}Response")
+                        } else {
+                            format!("{inline_type_prefix}Response{status}")
+                        };
+                        match body_schema_name(schema, inline_type_name, &mut components.schemas) {
+                            Ok(name) => Some(name),
+                            Err(e) => {
+                                tracing::warn!(
+                                    status,
+                                    "skipping operation with unsupported response body: {e:#}"
+                                );
+                                return None;
+                            }
+                        }
+                    }
+                    None => None,
+                };
+
+                Some(SuccessResponse {
+                    status,
+                    content_type,
+                    schema_name,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // Success responses without a body (e.g. `204`) don't keep the others' body from being used
+        let bodies: Vec<_> = success_responses
+            .iter()
+            .filter(|r| r.content_type.is_some())
+            .collect();
+        let (response_body_content_type, response_body_schema_name) = match &bodies[..] {
+            [first, rest @ ..]
+                if rest.iter().all(|r| {
+                    r.content_type == first.content_type && r.schema_name == first.schema_name
+                }) =>
+            {
+                (first.content_type, first.schema_name.clone())
+            }
+            [] => (None, None),
+            _ if filter.distinct_success_bodies => (None, None),
+            _ => {
+                tracing::warn!("skipping operation whose success responses have different bodies");
+                return None;
             }
+        };
 
-            schema_name
-        });
+        // Only error responses with a JSON body are kept, since their type is all that SDKs need
+        let error_responses = error_responses
+            .into_iter()
+            .filter_map(|(status, resp)| {
+                let (_, schema) = response_body(resp, components)
+                    .inspect_err(|e| tracing::warn!(status, "ignoring error response: {e:#}"))
+                    .ok()?;
+                let inline_type_name =
+                    format!("{inline_type_prefix}Error{}", status.to_upper_camel_case());
+                let schema_name =
+                    body_schema_name(schema?, inline_type_name, &mut components.schemas)
+                        .inspect_err(|e| {
+                            tracing::warn!(status, "ignoring error response body: {e:#}");
+                        })
+                        .ok()?;
+                Some((status, schema_name))
+            })
+            .collect();
 
-        let op_name = op_name.to_owned();
         let op = Operation {
             id: op_id,
             name: op_name,
+            version,
             description: op.description,
             deprecated: op.deprecated,
             method: method.to_owned(),
@@ -408,9 +703,13 @@ impl Operation {
             path_params,
             header_params,
             query_params,
+            request_body_content_type,
             request_body_schema_name,
             request_body_all_optional,
+            response_body_content_type,
             response_body_schema_name,
+            success_responses,
+            error_responses,
         };
         Some((res_path, op))
     }
@@ -418,6 +717,179 @@ impl Operation {
     pub(crate) fn has_query_or_header_params(&self) -> bool {
         !self.header_params.is_empty() || !self.query_params.is_empty()
     }
+
+    /// Imports that need to be in scope for the parameter type names of this operation in the
+    /// given language.
+    pub(crate) fn required_imports(&self, lang: &str) -> BTreeSet<&'static str> {
+        let query_param_types = self.query_params.iter().map(|p| &p.r#type);
+        let header_param_types = self.header_params.iter().map(|p| &p.r#type);
+        query_param_types
+            .chain(header_param_types)
+            .flat_map(|ty| ty.required_imports(lang))
+            .collect()
+    }
+}
+
+/// Get the API version of an operation, the path of the resource it belongs to, and the
+/// operation's name in code.
+fn resource_path_and_name(
+    resource_grouping: ResourceGrouping,
+    method: &str,
+    path: &str,
+    op: &openapi::Operation,
+) -> Option<(Option<String>, Vec<String>, String)> {
+    // Used as the operation name by all strategies that don't define their own
+    let last_op_id_part = || match &op.operation_id {
+        Some(op_id) => op_id
+            .rsplit('.')
+            .next()
+            .expect("split iter always contains at least one item")
+            .to_owned(),
+        None => op_name_from_method(method, path),
+    };
+
+    // Used as the version by all strategies except `OperationId`
+    let path_version = || {
+        path.split('/')
+            .find(|segment| is_api_version(segment))
+            .map(ToOwned::to_owned)
+    };
+
+    match resource_grouping {
+        ResourceGrouping::OperationId => {
+            let Some(op_id) = &op.operation_id else {
+                tracing::debug!("skipping operation without an operationId");
+                return None;
+            };
+            let mut op_id_parts_iter = op_id.split('.');
+            let version = op_id_parts_iter
+                .next()
+                .expect("split iter always contains at least one item");
+            let Some(op_name) = op_id_parts_iter.next_back() else {
+                tracing::debug!("skipping operation whose ID doesn't contain a period");
+                return None;
+            };
+
+            let res_path: Vec<_> = op_id_parts_iter.map(ToOwned::to_owned).collect();
+            if res_path.is_empty() {
+                tracing::debug!("skipping operation whose ID only contains one period");
+                return None;
+            }
+
+            if !is_api_version(version) {
+                tracing::warn!("found operation whose ID does not begin with a version");
+                return None;
+            }
+
+            Some((Some(version.to_owned()), res_path, op_name.to_owned()))
+        }
+        ResourceGrouping::Tag => {
+            let Some(tag) = op.tags.first() else {
+                tracing::debug!("skipping operation without tags");
+                return None;
+            };
+
+            Some((path_version(), vec![tag.to_snake_case()], last_op_id_part()))
+        }
+        ResourceGrouping::Path => {
+            let res_path: Vec<_> = path
+                .split('/')
+                .filter(|segment| {
+                    !segment.is_empty()
+                        && !segment.starts_with('{')
+                        && *segment != "api"
+                        && !is_api_version(segment)
+                })
+                .map(|segment| segment.to_snake_case())
+                .collect();
+            if res_path.is_empty() {
+                tracing::debug!("skipping operation whose path has no static segments");
+                return None;
+            }
+
+            Some((path_version(), res_path, last_op_id_part()))
+        }
+        ResourceGrouping::Extension => {
+            let Some(resource) = op.extensions.get("x-codegen-resource") else {
+                tracing::debug!("skipping operation without x-codegen-resource");
+                return None;
+            };
+            let Some(resource) = resource.as_str().filter(|r| !r.is_empty()) else {
+                tracing::warn!("x-codegen-resource must be a non-empty string");
+                return None;
+            };
+            let res_path = resource.split('.').map(ToOwned::to_owned).collect();
+
+            let op_name = match op.extensions.get("x-codegen-name") {
+                Some(name) => {
+                    let Some(name) = name.as_str() else {
+                        tracing::warn!("x-codegen-name must be a string");
+                        return None;
+                    };
+                    name.to_owned()
+                }
+                None => last_op_id_part(),
+            };
+
+            Some((path_version(), res_path, op_name))
+        }
+    }
+}
+
+/// Derive an operation name from the HTTP method, for operations without an `operationId`.
+///
+/// `GET` on a path ending in a parameter gets a single item, on any other path it lists them.
+fn op_name_from_method(method: &str, path: &str) -> String {
+    let ends_in_param = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .is_some_and(|segment| segment.starts_with('{'));
+    match method {
+        "get" if ends_in_param => "get",
+        "get" => "list",
+        "post" => "create",
+        "put" => "update",
+        other => other,
+    }
+    .to_owned()
+}
+
+/// Whether a string looks like an API version, e.g. `v1`.
+fn is_api_version(s: &str) -> bool {
+    s.strip_prefix('v')
+        .is_some_and(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Resolve an operation's parameters and merge in the ones defined on its path item.
+///
+/// As per the OpenAPI spec, an operation-level parameter overrides a path-level one with the same
+/// name and location.
+fn merge_parameters(
+    path_item_params: &[ReferenceOr<openapi::Parameter>],
+    op_params: Vec<ReferenceOr<openapi::Parameter>>,
+    components: &openapi::Components,
+) -> anyhow::Result<Vec<ReferenceOr<openapi::Parameter>>> {
+    let resolve = |p| resolve_ref(p, &components.parameters, "#/components/parameters/");
+
+    let op_params: Vec<_> = op_params
+        .into_iter()
+        .map(resolve)
+        .collect::<anyhow::Result<_>>()?;
+    let mut params = Vec::with_capacity(path_item_params.len() + op_params.len());
+    for p in path_item_params {
+        let p = resolve(p.clone())?;
+        let is_overridden = op_params.iter().any(|op_p| {
+            mem::discriminant(op_p) == mem::discriminant(&p)
+                && op_p.parameter_data_ref().name == p.parameter_data_ref().name
+        });
+        if !is_overridden {
+            params.push(p);
+        }
+    }
+    params.extend(op_params);
+
+    Ok(params.into_iter().map(ReferenceOr::Item).collect())
 }
 
 fn enforce_string_parameter(parameter_data: &openapi::ParameterData) -> anyhow::Result<()> {
@@ -434,44 +906,87 @@ fn enforce_string_parameter(parameter_data: &openapi::ParameterData) -> anyhow::
     Ok(())
 }
 
-fn response_body_schema_name(resp: ReferenceOr<openapi::Response>) -> Option<String> {
-    match resp {
-        ReferenceOr::Item(mut resp_body) => {
-            assert!(resp_body.extensions.is_empty());
-            if resp_body.content.is_empty() {
-                return None;
-            }
+/// Get the media type and, for JSON bodies, the schema of a response body.
+fn response_body(
+    resp: ReferenceOr<openapi::Response>,
+    components: &openapi::Components,
+) -> anyhow::Result<(Option<BodyContentType>, Option<Schema>)> {
+    let resp_body = resolve_ref(resp, &components.responses, "#/components/responses/")?;
+    if resp_body.content.is_empty() {
+        return Ok((None, None));
+    }
 
-            assert_eq!(resp_body.content.len(), 1);
-            let json_body = resp_body
-                .content
-                .swap_remove("application/json")
-                .expect("should have JSON body");
-            assert!(json_body.extensions.is_empty());
-            match json_body.schema.expect("no json body schema?!").json_schema {
-                Schema::Bool(_) => {
-                    tracing::error!("unexpected bool schema");
-                    None
-                }
-                Schema::Object(obj) => {
-                    if !obj.is_ref() {
-                        tracing::error!(?obj, "unexpected non-$ref json body schema");
-                    }
-                    get_schema_name(obj.reference.as_deref())
-                }
-            }
+    let (content_type, body) = BodyContentType::select(resp_body.content)?;
+    // multipart responses aren't a thing, in practice
+    if content_type != BodyContentType::Json {
+        return Ok((Some(content_type), None));
+    }
+
+    let Some(schema) = body.schema else {
+        tracing::warn!("ignoring the type of a JSON response body without schema");
+        return Ok((Some(content_type), None));
+    };
+    Ok((Some(content_type), Some(schema.json_schema)))
+}
+
+/// Get the name of the type of a request or response body.
+///
+/// Inline object schemas are added to the component schemas as `inline_type_name`, such that they
+/// get a named type like any `$ref`erenced schema.
+fn body_schema_name(
+    schema: Schema,
+    inline_type_name: String,
+    component_schemas: &mut IndexMap<String, openapi::SchemaObject>,
+) -> anyhow::Result<String> {
+    let Schema::Object(mut obj) = schema else {
+        bail!("unexpected bool schema");
+    };
+
+    if obj.is_ref() {
+        return get_schema_name(obj.reference.as_deref())
+            .with_context(|| format!("unsupported $ref `{}`", obj.reference.unwrap_or_default()));
+    }
+
+    // `properties` imply an object schema
+    if obj.instance_type.is_none() && obj.object.is_some() {
+        obj.instance_type = Some(InstanceType::Object.into());
+    }
+    ensure!(
+        obj.instance_type == Some(InstanceType::Object.into()),
+        "unsupported inline non-object body schema"
+    );
+
+    let json_schema = Schema::Object(obj);
+    match component_schemas.get(&inline_type_name) {
+        // The same inline schema was already seen, e.g. for another success status code
+        Some(existing) if existing.json_schema == json_schema => {}
+        Some(_) => {
+            bail!("inline body type name `{inline_type_name}` clashes with an existing schema");
         }
-        ReferenceOr::Reference { .. } => {
-            tracing::error!("$ref response bodies are not currently supported");
-            None
+        None => {
+            component_schemas.insert(
+                inline_type_name.clone(),
+                openapi::SchemaObject {
+                    json_schema,
+                    external_docs: None,
+                    example: None,
+                },
+            );
         }
     }
+
+    Ok(inline_type_name)
 }
 
 #[derive(Deserialize, Serialize)]
 struct HeaderParam {
     name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
     required: bool,
+    deprecated: bool,
+    #[serde(serialize_with = "serialize_field_type")]
+    r#type: FieldType,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -482,4 +997,6 @@ struct QueryParam {
     required: bool,
     #[serde(serialize_with = "serialize_field_type")]
     r#type: FieldType,
+    #[serde(default)]
+    validation: Validation,
 }
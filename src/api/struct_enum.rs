@@ -1,9 +1,16 @@
-use anyhow::{bail, ensure};
+use std::collections::BTreeMap;
+
+use anyhow::{Context as _, bail, ensure};
+use heck::ToUpperCamelCase as _;
 use schemars::schema::{ObjectValidation, Schema, SchemaObject};
+use serde::Deserialize;
 
-use crate::api::{
-    get_schema_name,
-    types::{EnumVariantType, Field, SimpleVariant, StructEnumRepr, TypeData},
+use crate::{
+    api::{
+        get_schema_name,
+        types::{EnumVariantType, Field, SimpleVariant, StructEnumRepr, Type, TypeData},
+    },
+    config::CustomFormats,
 };
 
 /// A wrapper around a Option<String>
@@ -24,63 +31,225 @@ impl SameString {
     }
 }
 
+/// The OpenAPI `discriminator` object.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Discriminator {
+    /// Name of the property that identifies the variant.
+    pub property_name: String,
+    /// Mapping from discriminator values to schema `$ref`s (or schema names).
+    #[serde(default)]
+    pub mapping: BTreeMap<String, String>,
+}
+
+impl Discriminator {
+    pub(crate) fn from_schema(s: &SchemaObject) -> anyhow::Result<Option<Self>> {
+        s.extensions
+            .get("discriminator")
+            .map(|d| serde_json::from_value(d.clone()).context("invalid discriminator"))
+            .transpose()
+    }
+
+    /// Get the discriminator value for the variant defined by schema `schema_name`.
+    ///
+    /// Defaults to the schema name if it's not part of the explicit mapping.
+    fn value_for(&self, schema_name: &str) -> String {
+        self.mapping
+            .iter()
+            .find(|(_, target)| {
+                get_schema_name(Some(target)).as_deref().unwrap_or(target) == schema_name
+            })
+            .map_or_else(|| schema_name.to_owned(), |(value, _)| value.clone())
+    }
+}
+
 impl TypeData {
+    /// Convert a `oneOf` with an explicit OpenAPI discriminator to an internally tagged enum.
+    pub(super) fn internally_tagged_struct_enum(
+        one_of: Vec<Schema>,
+        fields: Vec<Field>,
+        discriminator: Discriminator,
+        type_name: &str,
+        custom_formats: &CustomFormats,
+        inline_types: &mut Vec<Type>,
+    ) -> anyhow::Result<Self> {
+        let variants = one_of
+            .into_iter()
+            .map(|s| {
+                let Schema::Object(s) = s else {
+                    bail!("unsupported bool schema");
+                };
+
+                if let Some(schema_ref) = get_schema_name(s.reference.as_deref()) {
+                    return Ok(SimpleVariant {
+                        name: discriminator.value_for(&schema_ref),
+                        content: EnumVariantType::Ref {
+                            schema_ref: Some(schema_ref),
+                        },
+                    });
+                }
+
+                let mut obj = *s.object.context("unsupported: non-object variant")?;
+                let tag_schema = obj
+                    .properties
+                    .remove(&discriminator.property_name)
+                    .context("variant without discriminator property")?;
+                obj.required.remove(&discriminator.property_name);
+                let name = get_tag_value(get_schema_obj(&tag_schema)?)
+                    .context("unsupported discriminator property type")?;
+
+                let variant_type_name = format!("{type_name}{}", name.to_upper_camel_case());
+                let TypeData::Struct { fields } = TypeData::from_object_schema(
+                    obj,
+                    s.subschemas,
+                    None,
+                    &variant_type_name,
+                    custom_formats,
+                    inline_types,
+                )?
+                else {
+                    bail!("Expected obj to be a struct");
+                };
+
+                Ok(SimpleVariant {
+                    name,
+                    content: EnumVariantType::Struct { fields },
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self::StructEnum {
+            discriminator_field: discriminator.property_name,
+            repr: StructEnumRepr::InternallyTagged { variants },
+            fields,
+        })
+    }
+
     pub(super) fn inline_struct_enum(
         one_of: &Vec<Schema>,
         fields: &[Field],
+        type_name: &str,
+        custom_formats: &CustomFormats,
+        inline_types: &mut Vec<Type>,
     ) -> anyhow::Result<Self> {
         let mut discriminator_field = SameString(None);
-        let mut content_field = SameString(None);
-
-        let mut variants = vec![];
-
+        let mut parsed_variants = vec![];
         for s in one_of {
             let variant = get_obj_validation(s)?;
 
             let (variant_discriminator_name, discriminator) = get_discriminator(variant)?;
             discriminator_field.update(variant_discriminator_name)?;
+            parsed_variants.push((variant, discriminator));
+        }
+        let discriminator_field = discriminator_field
+            .inner()
+            .context("struct enum without variants")?;
 
-            let len = variant.properties.len();
-            ensure!(
-                (1..3).contains(&len),
-                "Found struct enum variant with {len} properties, expected 1 or 2"
-            );
-            if variant.properties.len() == 1 {
-                variants.push(SimpleVariant {
-                    name: discriminator,
-                    content: EnumVariantType::Ref { schema_ref: None },
-                });
-            } else {
-                let (variant_content_field, content) = get_content(variant)?;
-                content_field.update(variant_content_field)?;
-
-                variants.push(SimpleVariant {
-                    name: discriminator,
-                    content,
-                });
+        // If every variant has at most one property besides the discriminator, and that property
+        // is always the same object or `$ref`, the enum is adjacently tagged. Otherwise, all of the
+        // variant's properties sit next to the discriminator.
+        let mut content_field = SameString(None);
+        let adjacently_tagged = parsed_variants.iter().all(|(variant, _)| {
+            let mut content = variant
+                .properties
+                .iter()
+                .filter(|(name, _)| **name != discriminator_field);
+            match (content.next(), content.next()) {
+                (None, _) => true,
+                (Some((name, p)), None) => {
+                    is_content_schema(p) && content_field.update(name.clone()).is_ok()
+                }
+                (Some(_), Some(_)) => false,
             }
-        }
+        });
 
-        let s = Self::StructEnum {
-            discriminator_field: discriminator_field
-                .inner()
-                .expect("failed to fine discriminator field"),
-            fields: fields.to_vec(),
-            repr: StructEnumRepr::AdjacentlyTagged {
-                content_field: content_field.inner().expect("failed to fine content field"),
-                variants,
-            },
+        let mut variants = vec![];
+        let repr = match content_field.inner() {
+            Some(content_field) if adjacently_tagged => {
+                for (variant, discriminator) in parsed_variants {
+                    if variant.properties.len() == 1 {
+                        variants.push(SimpleVariant {
+                            name: discriminator,
+                            content: EnumVariantType::Ref { schema_ref: None },
+                        });
+                    } else {
+                        let variant_type_name =
+                            format!("{type_name}{}", discriminator.to_upper_camel_case());
+                        let (_, content) =
+                            get_content(variant, &variant_type_name, custom_formats, inline_types)?;
+
+                        variants.push(SimpleVariant {
+                            name: discriminator,
+                            content,
+                        });
+                    }
+                }
+
+                StructEnumRepr::AdjacentlyTagged {
+                    content_field,
+                    variants,
+                }
+            }
+            _ => {
+                for (variant, discriminator) in parsed_variants {
+                    let mut variant = variant.clone();
+                    variant.properties.remove(&discriminator_field);
+                    variant.required.remove(&discriminator_field);
+
+                    let content = if variant.properties.is_empty() {
+                        EnumVariantType::Ref { schema_ref: None }
+                    } else {
+                        let variant_type_name =
+                            format!("{type_name}{}", discriminator.to_upper_camel_case());
+                        let TypeData::Struct { fields } = TypeData::from_object_schema(
+                            variant,
+                            None,
+                            None,
+                            &variant_type_name,
+                            custom_formats,
+                            inline_types,
+                        )?
+                        else {
+                            bail!("Expected obj to be a struct");
+                        };
+                        EnumVariantType::Struct { fields }
+                    };
+
+                    variants.push(SimpleVariant {
+                        name: discriminator,
+                        content,
+                    });
+                }
+
+                StructEnumRepr::InternallyTagged { variants }
+            }
         };
 
-        Ok(s)
+        Ok(Self::StructEnum {
+            discriminator_field,
+            fields: fields.to_vec(),
+            repr,
+        })
     }
 }
 
-fn get_content(variant: &ObjectValidation) -> anyhow::Result<(String, EnumVariantType)> {
+fn get_content(
+    variant: &ObjectValidation,
+    variant_type_name: &str,
+    custom_formats: &CustomFormats,
+    inline_types: &mut Vec<Type>,
+) -> anyhow::Result<(String, EnumVariantType)> {
     for (p_name, p) in &variant.properties {
         let schema_obj = get_schema_obj(p)?;
         if let Some(obj) = &schema_obj.object {
-            let ty = TypeData::from_object_schema(*obj.clone(), None)?;
+            let ty = TypeData::from_object_schema(
+                *obj.clone(),
+                None,
+                None,
+                variant_type_name,
+                custom_formats,
+                inline_types,
+            )?;
             let TypeData::Struct { fields } = ty else {
                 anyhow::bail!("Expected obj to be a struct");
             };
@@ -101,6 +270,11 @@ fn get_content(variant: &ObjectValidation) -> anyhow::Result<(String, EnumVarian
     bail!("Failed to find content on struct enum")
 }
 
+/// Whether a variant property can hold the variant's content in an adjacently tagged enum.
+fn is_content_schema(s: &Schema) -> bool {
+    matches!(s, Schema::Object(o) if o.object.is_some() || o.reference.is_some())
+}
+
 fn get_discriminator(obj: &ObjectValidation) -> anyhow::Result<(String, String)> {
     let mut discriminator_field_name = None;
     let mut discriminator = None;
@@ -130,6 +304,14 @@ fn get_discriminator(obj: &ObjectValidation) -> anyhow::Result<(String, String)>
     Ok((discriminator_field_name, discriminator))
 }
 
+/// Get the value of a single-value enum or const schema.
+fn get_tag_value(s: &SchemaObject) -> Option<String> {
+    match (&s.const_value, s.enum_values.as_deref()) {
+        (Some(value), _) | (None, Some([value])) => value.as_str().map(ToOwned::to_owned),
+        _ => None,
+    }
+}
+
 fn get_schema_obj(s: &Schema) -> anyhow::Result<&SchemaObject> {
     match s {
         Schema::Bool(_) => bail!("unsupported bool schema"),
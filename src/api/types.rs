@@ -6,7 +6,9 @@ use std::{
 
 use aide::openapi;
 use anyhow::{Context as _, bail, ensure};
+use heck::ToUpperCamelCase as _;
 use indexmap::IndexMap;
+use minijinja::value::{Kwargs, from_args};
 use schemars::schema::{
     InstanceType, ObjectValidation, Schema, SchemaObject, SingleOrVec, SubschemaValidation,
 };
@@ -15,8 +17,13 @@ use serde::{Deserialize, Serialize};
 use super::{
     get_schema_name,
     resources::{self, Resources},
+    struct_enum::Discriminator,
+};
+use crate::{
+    IncludeMode,
+    config::{CustomFormats, RUST_CONFIG_GLOBAL, RustConfig, RustSetType},
+    type_mapping,
 };
-use crate::IncludeMode;
 
 /// Named types referenced by API operations.
 ///
@@ -28,6 +35,8 @@ pub(crate) fn from_referenced_components(
     schemas: &mut IndexMap<String, openapi::SchemaObject>,
     webhooks: &[String],
     include_mode: IncludeMode,
+    custom_formats: &CustomFormats,
+    untagged_enums: bool,
 ) -> Types {
     let mut referenced_components: Vec<&str> = match include_mode {
         IncludeMode::OnlyPublic | IncludeMode::PublicAndHidden | IncludeMode::OnlyHidden => {
@@ -37,6 +46,10 @@ pub(crate) fn from_referenced_components(
     };
     referenced_components.extend(resources::referenced_components(res));
 
+    // Schemas are removed from `schemas` as they get converted, but `allOf` compositions may still
+    // need to look them up afterwards
+    let all_schemas = schemas.clone();
+
     let mut types = BTreeMap::new();
     let mut add_type = |schema_name: &str, extra_components: &mut BTreeSet<_>| {
         let Some(s) = schemas.swap_remove(schema_name) else {
@@ -52,15 +65,60 @@ pub(crate) fn from_referenced_components(
             Schema::Object(o) => o,
         };
 
-        match Type::from_schema(schema_name.to_owned(), obj) {
-            Ok(ty) => {
-                extra_components.extend(
-                    ty.referenced_components()
-                        .into_iter()
-                        .filter(|&c| c != schema_name && !types.contains_key(c))
-                        .map(ToOwned::to_owned),
-                );
-                types.insert(schema_name.to_owned(), ty);
+        // Nullability is a property of the fields referencing the schema, not of its type
+        let (mut obj, _) = strip_null(obj);
+        // `oneOf: [{ $ref }, { type: null }]` leaves just the reference, which is treated like a
+        // single-element `allOf`
+        if obj.is_ref() {
+            obj = SchemaObject {
+                metadata: obj.metadata.take(),
+                subschemas: Some(Box::new(SubschemaValidation {
+                    all_of: Some(vec![Schema::Object(obj)]),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            };
+        }
+
+        let (obj, parents) = match flatten_all_of(obj, &all_schemas) {
+            Ok(res) => res,
+            Err(e) => {
+                tracing::warn!(schema_name, "unsupported schema: {e:#}");
+                return;
+            }
+        };
+
+        let mut inline_types = Vec::new();
+        match Type::from_schema(
+            schema_name.to_owned(),
+            obj,
+            custom_formats,
+            &mut inline_types,
+        ) {
+            Ok(mut ty) => {
+                ty.parents = parents;
+
+                if let Some(clashing) = inline_types
+                    .iter()
+                    .find(|t| types.contains_key(&t.name) || schemas.contains_key(&t.name))
+                {
+                    tracing::warn!(
+                        schema_name,
+                        inline_type_name = clashing.name,
+                        "unsupported schema: name of nested inline type clashes with another schema"
+                    );
+                    return;
+                }
+
+                for ty in inline_types.into_iter().chain([ty]) {
+                    extra_components.extend(
+                        ty.referenced_components()
+                            .into_iter()
+                            .filter(|&c| c != ty.name && !types.contains_key(c))
+                            .map(ToOwned::to_owned),
+                    );
+                    types.insert(ty.name.clone(), ty);
+                }
             }
             Err(e) => {
                 tracing::warn!(schema_name, "unsupported schema: {e:#}");
@@ -68,34 +126,165 @@ pub(crate) fn from_referenced_components(
         }
     };
 
-    let mut extra_components: BTreeSet<_> = referenced_components
+    let roots: Vec<_> = referenced_components
         .into_iter()
         .map(ToOwned::to_owned)
         .collect();
+    let mut extra_components: BTreeSet<_> = roots.iter().cloned().collect();
     while let Some(c) = extra_components.pop_first() {
         add_type(&c, &mut extra_components);
     }
 
+    if !untagged_enums {
+        json_object_for_untagged_enums(&mut types, roots);
+    }
     types
 }
 
+/// Replace field types referencing untagged enums with [`FieldType::JsonObject`], and drop the
+/// types that are no longer referenced afterwards.
+///
+/// None of the bundled templates can render untagged enums yet, so this keeps their output the way
+/// it was before untagged enums were added to the IR, unless `--untagged-enums` is passed.
+fn json_object_for_untagged_enums(types: &mut Types, roots: Vec<String>) {
+    let untagged_enums: BTreeSet<_> = types
+        .values()
+        .filter(|ty| matches!(ty.data, TypeData::UntaggedEnum { .. }))
+        .map(|ty| ty.name.clone())
+        .collect();
+    if untagged_enums.is_empty() {
+        return;
+    }
+
+    for ty in types.values_mut() {
+        for field_type in ty.field_types_mut() {
+            field_type.replace_refs_with_json_object(&untagged_enums);
+        }
+    }
+
+    let mut reachable = BTreeSet::new();
+    let mut queue = roots;
+    while let Some(name) = queue.pop() {
+        if let Some(ty) = types.get(&name)
+            && reachable.insert(name)
+        {
+            queue.extend(
+                ty.referenced_components()
+                    .into_iter()
+                    .map(ToOwned::to_owned),
+            );
+        }
+    }
+    types.retain(|name, _| reachable.contains(name));
+}
+
+/// Flatten a schema composed through `allOf` into a single object schema.
+///
+/// Returns the flattened schema, and the names of the `$ref`erenced schemas it is composed of.
+fn flatten_all_of(
+    mut s: SchemaObject,
+    schemas: &IndexMap<String, openapi::SchemaObject>,
+) -> anyhow::Result<(SchemaObject, Vec<String>)> {
+    let Some(all_of) = s.subschemas.as_mut().and_then(|sub| sub.all_of.take()) else {
+        return Ok((s, Vec::new()));
+    };
+
+    let mut parents = Vec::new();
+    let mut merged = ObjectValidation::default();
+    for sub in all_of {
+        let Schema::Object(sub) = sub else {
+            bail!("unsupported: bool schema in allOf");
+        };
+
+        let sub = match get_schema_name(sub.reference.as_deref()) {
+            Some(name) => {
+                let parent = schemas
+                    .get(&name)
+                    .with_context(|| format!("allOf schema `{name}` not found"))?;
+                let Schema::Object(parent) = &parent.json_schema else {
+                    bail!("unsupported: bool schema `{name}` in allOf");
+                };
+                parents.push(name);
+                // the parent may be composed through allOf as well
+                flatten_all_of(parent.clone(), schemas)?.0
+            }
+            None => flatten_all_of(sub, schemas)?.0,
+        };
+
+        ensure!(
+            sub.instance_type.is_none() || sub.instance_type == Some(InstanceType::Object.into()),
+            "unsupported: non-object allOf subschema"
+        );
+        ensure!(
+            sub.subschemas.is_none(),
+            "unsupported: allOf subschema with subschemas"
+        );
+        if let Some(obj) = sub.object {
+            merge_object_validation(&mut merged, *obj);
+        }
+    }
+
+    // fields defined next to `allOf`
+    if let Some(obj) = s.object.take() {
+        merge_object_validation(&mut merged, *obj);
+    }
+
+    s.instance_type = Some(InstanceType::Object.into());
+    s.object = Some(Box::new(merged));
+    Ok((s, parents))
+}
+
+fn merge_object_validation(target: &mut ObjectValidation, other: ObjectValidation) {
+    target.properties.extend(other.properties);
+    target.required.extend(other.required);
+    target.pattern_properties.extend(other.pattern_properties);
+    target.additional_properties = target
+        .additional_properties
+        .take()
+        .or(other.additional_properties);
+    target.property_names = target.property_names.take().or(other.property_names);
+    target.max_properties = target.max_properties.or(other.max_properties);
+    target.min_properties = target.min_properties.or(other.min_properties);
+}
+
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Type {
     name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     description: Option<String>,
     deprecated: bool,
+    /// Names of the types this type was composed of through `allOf`, if any.
+    ///
+    /// All of their fields are also part of this type.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    parents: Vec<String>,
     #[serde(flatten)]
     data: TypeData,
 }
 
 impl Type {
-    pub(crate) fn from_schema(name: String, s: SchemaObject) -> anyhow::Result<Self> {
+    /// Convert a schema to a type.
+    ///
+    /// Types synthesized from nested inline object schemas are added to `inline_types`.
+    pub(crate) fn from_schema(
+        name: String,
+        s: SchemaObject,
+        custom_formats: &CustomFormats,
+        inline_types: &mut Vec<Type>,
+    ) -> anyhow::Result<Self> {
+        let discriminator = Discriminator::from_schema(&s)?;
         let data = match s.instance_type {
             Some(SingleOrVec::Single(it)) => match *it {
                 InstanceType::Object => {
                     let obj = s.object.unwrap_or_default();
-                    TypeData::from_object_schema(*obj, s.subschemas)?
+                    TypeData::from_object_schema(
+                        *obj,
+                        s.subschemas,
+                        discriminator,
+                        &name,
+                        custom_formats,
+                        inline_types,
+                    )?
                 }
                 InstanceType::Integer => {
                     let enum_varnames = s
@@ -125,7 +314,27 @@ impl Type {
                 _ => bail!("unsupported type {it:?}"),
             },
             Some(SingleOrVec::Vec(_)) => bail!("unsupported: multiple types"),
-            None => bail!("unsupported: no type"),
+            // a discriminator implies an object type
+            None if discriminator.is_some() => {
+                let obj = s.object.unwrap_or_default();
+                TypeData::from_object_schema(
+                    *obj,
+                    s.subschemas,
+                    discriminator,
+                    &name,
+                    custom_formats,
+                    inline_types,
+                )?
+            }
+            None => {
+                let sub = s.subschemas.context("unsupported: no type")?;
+                let variants = match (sub.one_of, sub.any_of) {
+                    (Some(variants), None) | (None, Some(variants)) => variants,
+                    (Some(_), Some(_)) => bail!("unsupported: both oneOf and anyOf"),
+                    (None, None) => bail!("unsupported: no type"),
+                };
+                TypeData::untagged_enum(variants, &name, custom_formats, inline_types)?
+            }
         };
 
         let metadata = s.metadata.unwrap_or_default();
@@ -134,12 +343,13 @@ impl Type {
             name,
             description: metadata.description,
             deprecated: metadata.deprecated,
+            parents: Vec::new(),
             data,
         })
     }
 
     pub(crate) fn referenced_components(&self) -> BTreeSet<&str> {
-        match &self.data {
+        let mut res = match &self.data {
             TypeData::Struct { fields } => fields_referenced_schemas(fields),
             TypeData::StringEnum { .. } => BTreeSet::new(),
             TypeData::IntegerEnum { .. } => BTreeSet::new(),
@@ -148,10 +358,64 @@ impl Type {
                 res.append(&mut fields_referenced_schemas(fields));
                 res
             }
+            TypeData::UntaggedEnum { variants } => variants
+                .iter()
+                .filter_map(|v| v.r#type.referenced_schema())
+                .collect(),
+        };
+        res.extend(self.parents.iter().map(|p| p.as_str()));
+        res
+    }
+
+    fn field_types_mut(&mut self) -> Vec<&mut FieldType> {
+        match &mut self.data {
+            TypeData::Struct { fields } => fields.iter_mut().map(|f| &mut f.r#type).collect(),
+            TypeData::StringEnum { .. } | TypeData::IntegerEnum { .. } => Vec::new(),
+            TypeData::StructEnum { repr, fields, .. } => {
+                let (StructEnumRepr::InternallyTagged { variants }
+                | StructEnumRepr::AdjacentlyTagged { variants, .. }) = repr;
+                let variant_fields = variants.iter_mut().flat_map(|v| match &mut v.content {
+                    EnumVariantType::Struct { fields } => fields.as_mut_slice(),
+                    EnumVariantType::Ref { .. } => &mut [],
+                });
+                fields
+                    .iter_mut()
+                    .chain(variant_fields)
+                    .map(|f| &mut f.r#type)
+                    .collect()
+            }
+            TypeData::UntaggedEnum { variants } => {
+                variants.iter_mut().map(|v| &mut v.r#type).collect()
+            }
+        }
+    }
+
+    /// Imports that need to be in scope for the type names of this type's fields in the given
+    /// language.
+    pub(crate) fn required_imports(&self, lang: &str) -> BTreeSet<&'static str> {
+        match &self.data {
+            TypeData::Struct { fields } => fields_required_imports(fields, lang),
+            TypeData::StringEnum { .. } | TypeData::IntegerEnum { .. } => BTreeSet::new(),
+            TypeData::StructEnum { repr, fields, .. } => {
+                let mut res = repr.required_imports(lang);
+                res.append(&mut fields_required_imports(fields, lang));
+                res
+            }
+            TypeData::UntaggedEnum { variants } => variants
+                .iter()
+                .flat_map(|v| v.r#type.required_imports(lang))
+                .collect(),
         }
     }
 }
 
+fn fields_required_imports(fields: &[Field], lang: &str) -> BTreeSet<&'static str> {
+    fields
+        .iter()
+        .flat_map(|f| f.r#type.required_imports(lang))
+        .collect()
+}
+
 fn fields_referenced_schemas(fields: &[Field]) -> BTreeSet<&str> {
     fields
         .iter()
@@ -182,12 +446,25 @@ pub(crate) enum TypeData {
         /// Variant-independent fields.
         fields: Vec<Field>,
     },
+    /// An enum without a discriminator, where the variant is identified by which of the variant
+    /// types a value matches.
+    UntaggedEnum {
+        variants: Vec<UntaggedVariant>,
+    },
 }
 
 impl TypeData {
+    /// Convert an object schema to type data.
+    ///
+    /// `type_name` is used to name types synthesized from nested inline object schemas, which are
+    /// added to `inline_types`.
     pub(super) fn from_object_schema(
         obj: ObjectValidation,
         subschemas: Option<Box<SubschemaValidation>>,
+        discriminator: Option<Discriminator>,
+        type_name: &str,
+        custom_formats: &CustomFormats,
+        inline_types: &mut Vec<Type>,
     ) -> anyhow::Result<Self> {
         ensure!(
             obj.additional_properties.is_none(),
@@ -201,36 +478,116 @@ impl TypeData {
         );
         ensure!(obj.property_names.is_none(), "unsupported: propertyNames");
 
+        // Without `oneOf`, a discriminator only tells apart the schemas extending this one through
+        // `allOf`, so the schema itself is a plain struct that keeps the discriminator field
+        let discriminator =
+            discriminator.filter(|_| subschemas.as_ref().is_some_and(|sub| sub.one_of.is_some()));
+
         let fields: Vec<_> = obj
             .properties
             .into_iter()
+            // with a discriminator, the tag field is represented by the enum itself
+            .filter(|(name, _)| {
+                discriminator
+                    .as_ref()
+                    .is_none_or(|d| d.property_name != *name)
+            })
             .map(|(name, schema)| {
-                Field::from_schema(name.clone(), schema, obj.required.contains(&name))
-                    .with_context(|| format!("unsupported field `{name}`"))
+                let required = obj.required.contains(&name);
+                Field::from_schema(
+                    name.clone(),
+                    schema,
+                    required,
+                    type_name,
+                    custom_formats,
+                    inline_types,
+                )
+                .with_context(|| format!("unsupported field `{name}`"))
             })
             .collect::<anyhow::Result<_>>()?;
 
         if let Some(sub) = subschemas {
             ensure!(sub.all_of.is_none(), "unsupported: allOf subschema");
-            ensure!(sub.any_of.is_none(), "unsupported: anyOf subschema");
             ensure!(sub.not.is_none(), "unsupported: not subschema");
             ensure!(sub.if_schema.is_none(), "unsupported: if subschema");
             ensure!(sub.then_schema.is_none(), "unsupported: then subschema");
             ensure!(sub.else_schema.is_none(), "unsupported: else subschema");
 
-            if let Some(one_of) = sub.one_of {
-                return Self::inline_struct_enum(&one_of, &fields);
-                // if let Ok(struct_enum) = Self::inline_struct_enum(&one_of, &fields) {
-                //     return Ok(struct_enum);
-                // }
+            if let Some(any_of) = sub.any_of {
+                ensure!(sub.one_of.is_none(), "unsupported: both oneOf and anyOf");
+                ensure!(
+                    fields.is_empty(),
+                    "unsupported: anyOf with sibling properties"
+                );
+                return Self::untagged_enum(any_of, type_name, custom_formats, inline_types);
+            }
+
+            if let Some(discriminator) = discriminator
+                && let Some(one_of) = sub.one_of
+            {
+                return Self::internally_tagged_struct_enum(
+                    one_of,
+                    fields,
+                    discriminator,
+                    type_name,
+                    custom_formats,
+                    inline_types,
+                );
+            }
 
-                // return Self::struct_enum(one_of, fields);
+            if let Some(one_of) = sub.one_of {
+                // Variants that are all inline objects are expected to carry a discriminator,
+                // anything else is matched by shape
+                if one_of.iter().all(is_inline_object_schema) {
+                    return Self::inline_struct_enum(
+                        &one_of,
+                        &fields,
+                        type_name,
+                        custom_formats,
+                        inline_types,
+                    );
+                }
+                ensure!(
+                    fields.is_empty(),
+                    "unsupported: oneOf with sibling properties"
+                );
+                return Self::untagged_enum(one_of, type_name, custom_formats, inline_types);
             }
         }
 
         Ok(Self::Struct { fields })
     }
 
+    /// Convert the subschemas of a `oneOf` / `anyOf` without discriminator to an untagged enum.
+    ///
+    /// Inline object variants are converted to types named `{type_name}Variant{n}`.
+    fn untagged_enum(
+        variants: Vec<Schema>,
+        type_name: &str,
+        custom_formats: &CustomFormats,
+        inline_types: &mut Vec<Type>,
+    ) -> anyhow::Result<Self> {
+        let mut names = BTreeSet::new();
+        let variants = variants
+            .into_iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let inline_type_name = format!("{type_name}Variant{}", i + 1);
+                let r#type =
+                    FieldType::from_schema(s, &inline_type_name, custom_formats, inline_types)
+                        .with_context(|| format!("unsupported variant {}", i + 1))?;
+                let name = r#type.untagged_variant_name();
+                ensure!(
+                    names.insert(name.clone()),
+                    "multiple variants with the same name `{name}`"
+                );
+                Ok(UntaggedVariant { name, r#type })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self::UntaggedEnum { variants })
+    }
+
     fn from_string_enum(values: Vec<serde_json::Value>) -> anyhow::Result<TypeData> {
         Ok(Self::StringEnum {
             values: values
@@ -279,6 +636,14 @@ impl TypeData {
 #[serde(tag = "repr", rename_all = "snake_case")]
 pub(crate) enum StructEnumRepr {
     // add more variants here to support other enum representations
+    /// The discriminator field and the variant-specific fields sit next to each other.
+    InternallyTagged {
+        /// Enum variants.
+        ///
+        /// Every variant has a discriminator value that's stored in the discriminator field to
+        /// identify the variant.
+        variants: Vec<SimpleVariant>,
+    },
     AdjacentlyTagged {
         /// Name of the field that contains the variant-specific fields.
         content_field: String,
@@ -294,13 +659,27 @@ pub(crate) enum StructEnumRepr {
 impl StructEnumRepr {
     fn referenced_components(&self) -> BTreeSet<&str> {
         match self {
-            StructEnumRepr::AdjacentlyTagged { variants, .. } => variants
+            StructEnumRepr::InternallyTagged { variants }
+            | StructEnumRepr::AdjacentlyTagged { variants, .. } => variants
                 .iter()
-                .filter_map(|v| match &v.content {
-                    EnumVariantType::Struct { fields } => {
-                        fields.iter().find_map(|f| f.r#type.referenced_schema())
+                .flat_map(|v| match &v.content {
+                    EnumVariantType::Struct { fields } => fields_referenced_schemas(fields),
+                    EnumVariantType::Ref { schema_ref } => {
+                        schema_ref.as_deref().into_iter().collect()
                     }
-                    EnumVariantType::Ref { schema_ref } => schema_ref.as_deref(),
+                })
+                .collect(),
+        }
+    }
+
+    fn required_imports(&self, lang: &str) -> BTreeSet<&'static str> {
+        match self {
+            StructEnumRepr::InternallyTagged { variants }
+            | StructEnumRepr::AdjacentlyTagged { variants, .. } => variants
+                .iter()
+                .flat_map(|v| match &v.content {
+                    EnumVariantType::Struct { fields } => fields_required_imports(fields, lang),
+                    EnumVariantType::Ref { .. } => BTreeSet::new(),
                 })
                 .collect(),
         }
@@ -319,34 +698,156 @@ pub(crate) struct Field {
     required: bool,
     nullable: bool,
     deprecated: bool,
+    #[serde(default)]
+    validation: Validation,
 }
 
 impl Field {
-    fn from_schema(name: String, s: Schema, required: bool) -> anyhow::Result<Self> {
+    fn from_schema(
+        name: String,
+        s: Schema,
+        required: bool,
+        parent_type_name: &str,
+        custom_formats: &CustomFormats,
+        inline_types: &mut Vec<Type>,
+    ) -> anyhow::Result<Self> {
         let obj = match s {
             Schema::Bool(_) => bail!("unsupported bool schema"),
             Schema::Object(o) => o,
         };
         let metadata = obj.metadata.clone().unwrap_or_default();
 
-        let nullable = obj
+        let nullable_ext = obj
             .extensions
             .get("nullable")
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
+        let (obj, nullable_type) = strip_null(obj);
+        let nullable = nullable_ext || nullable_type;
+        let validation = Validation::from_schema_object(&obj);
 
+        let inline_type_name = format!("{parent_type_name}{}", name.to_upper_camel_case());
         Ok(Self {
             name,
-            r#type: FieldType::from_schema_object(obj)?,
+            r#type: FieldType::from_schema_object(
+                obj,
+                &inline_type_name,
+                custom_formats,
+                inline_types,
+            )?,
             default: metadata.default,
             description: metadata.description,
             required,
             nullable,
             deprecated: metadata.deprecated,
+            validation,
         })
     }
 }
 
+/// Strip OpenAPI 3.1 style nullability from a schema.
+///
+/// Both `type: [T, "null"]` and a `oneOf` / `anyOf` of some schema and `type: "null"` are converted
+/// to the non-null schema. Also returns whether the schema was nullable.
+fn strip_null(mut obj: SchemaObject) -> (SchemaObject, bool) {
+    if let Some(SingleOrVec::Vec(types)) = &mut obj.instance_type
+        && types.contains(&InstanceType::Null)
+    {
+        types.retain(|ty| *ty != InstanceType::Null);
+        if let [ty] = types[..] {
+            obj.instance_type = Some(ty.into());
+        }
+        return (obj, true);
+    }
+
+    if let Some(sub) = &obj.subschemas
+        && let (Some(variants), None) | (None, Some(variants)) = (&sub.one_of, &sub.any_of)
+        && let [a, b] = &variants[..]
+    {
+        let inner = match (is_null_schema(a), is_null_schema(b)) {
+            (false, true) => a,
+            (true, false) => b,
+            _ => return (obj, false),
+        };
+        if let Schema::Object(inner) = inner {
+            let mut inner = inner.clone();
+            inner.metadata = inner.metadata.or(obj.metadata);
+            return (inner, true);
+        }
+    }
+
+    (obj, false)
+}
+
+fn is_null_schema(s: &Schema) -> bool {
+    matches!(s, Schema::Object(o) if o.instance_type == Some(InstanceType::Null.into()))
+}
+
+/// Validation constraints of a field or parameter value.
+///
+/// Only the constraints present in the schema are serialized.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub(crate) struct Validation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    minimum: Option<serde_json::Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maximum: Option<serde_json::Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    multiple_of: Option<serde_json::Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_items: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_items: Option<u32>,
+}
+
+impl Validation {
+    pub(crate) fn from_schema_object(obj: &SchemaObject) -> Self {
+        let mut res = Self::default();
+        if let Some(string) = &obj.string {
+            res.min_length = string.min_length;
+            res.max_length = string.max_length;
+            res.pattern = string.pattern.clone();
+        }
+        if let Some(number) = &obj.number {
+            res.minimum = number.minimum.and_then(json_number);
+            res.maximum = number.maximum.and_then(json_number);
+            res.multiple_of = number.multiple_of.and_then(json_number);
+        }
+        if let Some(array) = &obj.array {
+            res.min_items = array.min_items;
+            res.max_items = array.max_items;
+        }
+        res
+    }
+
+    /// Get the validation constraints of a parameter's schema.
+    pub(crate) fn from_parameter_format(format: &openapi::ParameterSchemaOrContent) -> Self {
+        match format {
+            openapi::ParameterSchemaOrContent::Schema(s) => match &s.json_schema {
+                Schema::Object(obj) => Self::from_schema_object(obj),
+                Schema::Bool(_) => Self::default(),
+            },
+            openapi::ParameterSchemaOrContent::Content(_) => Self::default(),
+        }
+    }
+}
+
+/// Convert a numeric constraint to JSON, keeping integral values as integers so templates don't
+/// render `1.0` as the bound of an integer field.
+fn json_number(n: f64) -> Option<serde_json::Number> {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Some((n as i64).into())
+    } else {
+        serde_json::Number::from_f64(n)
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub(crate) enum EnumVariantType {
@@ -359,6 +860,14 @@ pub(crate) enum EnumVariantType {
     },
 }
 
+#[derive(Deserialize, Serialize)]
+pub(crate) struct UntaggedVariant {
+    /// Name of the variant, derived from its type.
+    pub name: String,
+    #[serde(serialize_with = "serialize_field_type")]
+    pub r#type: FieldType,
+}
+
 #[derive(Deserialize, Serialize)]
 pub(crate) struct SimpleVariant {
     /// Discriminator value that identifies this variant.
@@ -379,9 +888,27 @@ pub(crate) enum FieldType {
     Int32,
     Int64,
     UInt64,
+    Float32,
+    Float64,
+    /// An arbitrary-precision decimal number.
+    Decimal,
     String,
     DateTime,
+    /// A calendar date without time, e.g. `2024-01-31`.
+    Date,
+    /// A time of day, e.g. `13:45:00`.
+    Time,
+    /// An ISO 8601 duration, e.g. `P3DT4H`.
+    Duration,
     Uri,
+    Uuid,
+    Email,
+    Ipv4,
+    Ipv6,
+    /// Base64-encoded binary data.
+    Base64,
+    /// Raw binary data.
+    Binary,
     /// A JSON object with arbitrary field values.
     JsonObject,
     /// A regular old list.
@@ -407,25 +934,60 @@ pub(crate) enum FieldType {
     StringConst {
         value: String,
     },
+
+    /// A string, integer or number with a format declared in [`CustomFormats`].
+    Custom {
+        format: String,
+        /// The type of the schema without its format.
+        base: Arc<FieldType>,
+        /// Type names by template language.
+        typenames: BTreeMap<String, String>,
+    },
 }
 
 impl FieldType {
-    pub(crate) fn from_openapi(format: openapi::ParameterSchemaOrContent) -> anyhow::Result<Self> {
+    pub(crate) fn from_openapi(
+        format: openapi::ParameterSchemaOrContent,
+        custom_formats: &CustomFormats,
+    ) -> anyhow::Result<Self> {
         let openapi::ParameterSchemaOrContent::Schema(s) = format else {
             bail!("found unexpected 'content' data format");
         };
-        Self::from_schema(s.json_schema)
+
+        let mut inline_types = Vec::new();
+        let ty = Self::from_schema(s.json_schema, "", custom_formats, &mut inline_types)?;
+        ensure!(
+            inline_types.is_empty(),
+            "unsupported: inline object as parameter type"
+        );
+        Ok(ty)
     }
 
-    fn from_schema(s: Schema) -> anyhow::Result<Self> {
+    fn from_schema(
+        s: Schema,
+        inline_type_name: &str,
+        custom_formats: &CustomFormats,
+        inline_types: &mut Vec<Type>,
+    ) -> anyhow::Result<Self> {
         let Schema::Object(obj) = s else {
             bail!("found unexpected `true` schema");
         };
 
-        Self::from_schema_object(obj)
+        Self::from_schema_object(obj, inline_type_name, custom_formats, inline_types)
     }
 
-    fn from_schema_object(obj: SchemaObject) -> anyhow::Result<Self> {
+    /// Convert a schema to a field type.
+    ///
+    /// Inline object schemas with properties are converted to a type named `inline_type_name`,
+    /// which is added to `inline_types` and referenced through [`FieldType::SchemaRef`].
+    fn from_schema_object(
+        obj: SchemaObject,
+        inline_type_name: &str,
+        custom_formats: &CustomFormats,
+        inline_types: &mut Vec<Type>,
+    ) -> anyhow::Result<Self> {
+        // nullability is only tracked for fields, see `Field::from_schema`
+        let (obj, _) = strip_null(obj);
         let result = match &obj.instance_type {
             Some(SingleOrVec::Single(ty)) => match **ty {
                 InstanceType::Boolean => Self::Bool,
@@ -437,8 +999,20 @@ impl FieldType {
                     Some("int" | "int64") => Self::Int64,
                     // FIXME: Get rid of uint in the spec..
                     Some("uint" | "uint64") => Self::UInt64,
+                    Some(f) if custom_formats.contains_key(f) => {
+                        Self::custom(f, Self::Int64, custom_formats)
+                    }
                     f => bail!("unsupported integer format: `{f:?}`"),
                 },
+                InstanceType::Number => match obj.format.as_deref() {
+                    Some("float") => Self::Float32,
+                    None | Some("double") => Self::Float64,
+                    Some("decimal") => Self::Decimal,
+                    Some(f) if custom_formats.contains_key(f) => {
+                        Self::custom(f, Self::Float64, custom_formats)
+                    }
+                    f => bail!("unsupported number format: `{f:?}`"),
+                },
                 InstanceType::String => {
                     // String consts are the only const / enum values we support, for now.
                     // Early return so we don't hit the checks for these two below.
@@ -461,7 +1035,19 @@ impl FieldType {
                     match obj.format.as_deref() {
                         None | Some("color") => Self::String,
                         Some("date-time") => Self::DateTime,
+                        Some("date") => Self::Date,
+                        Some("time") => Self::Time,
+                        Some("duration") => Self::Duration,
                         Some("uri") => Self::Uri,
+                        Some("uuid") => Self::Uuid,
+                        Some("email") => Self::Email,
+                        Some("ipv4") => Self::Ipv4,
+                        Some("ipv6") => Self::Ipv6,
+                        Some("byte") => Self::Base64,
+                        Some("binary") => Self::Binary,
+                        Some(f) if custom_formats.contains_key(f) => {
+                            Self::custom(f, Self::String, custom_formats)
+                        }
                         Some(f) => bail!("unsupported string format: `{f:?}`"),
                     }
                 }
@@ -474,13 +1060,29 @@ impl FieldType {
                             bail!("unsupported multi-typed array parameter: `{types:?}`")
                         }
                     };
-                    let inner = Arc::new(Self::from_schema(*inner)?);
+                    let inner = Arc::new(Self::from_schema(
+                        *inner,
+                        inline_type_name,
+                        custom_formats,
+                        inline_types,
+                    )?);
                     if array.unique_items == Some(true) {
                         Self::Set { inner }
                     } else {
                         Self::List { inner }
                     }
                 }
+                InstanceType::Object
+                    if obj
+                        .object
+                        .as_ref()
+                        .is_some_and(|o| !o.properties.is_empty()) =>
+                {
+                    let name = inline_type_name.to_owned();
+                    let ty = Type::from_schema(name.clone(), obj, custom_formats, inline_types)?;
+                    inline_types.push(ty);
+                    return Ok(Self::SchemaRef { name });
+                }
                 InstanceType::Object => {
                     let obj = obj
                         .object
@@ -509,7 +1111,12 @@ impl FieldType {
                         Schema::Bool(true) => Self::JsonObject,
                         Schema::Bool(false) => bail!("unsupported `additional_properties: false`"),
                         Schema::Object(schema_object) => {
-                            let value_ty = Arc::new(Self::from_schema_object(schema_object)?);
+                            let value_ty = Arc::new(Self::from_schema_object(
+                                schema_object,
+                                inline_type_name,
+                                custom_formats,
+                                inline_types,
+                            )?);
                             Self::Map { value_ty }
                         }
                     }
@@ -521,7 +1128,15 @@ impl FieldType {
             }
             None => match get_schema_name(obj.reference.as_deref()) {
                 Some(name) => Self::SchemaRef { name },
-                None => bail!("unsupported type-less parameter"),
+                // `allOf` with a single `$ref` is commonly used to add a description to a `$ref`
+                None => match obj.subschemas.as_deref().and_then(|s| s.all_of.as_deref()) {
+                    Some([Schema::Object(sub)]) if sub.is_ref() => {
+                        let name = get_schema_name(sub.reference.as_deref())
+                            .context("unsupported allOf $ref")?;
+                        Self::SchemaRef { name }
+                    }
+                    _ => bail!("unsupported type-less parameter"),
+                },
             },
         };
 
@@ -532,17 +1147,73 @@ impl FieldType {
         Ok(result)
     }
 
+    fn custom(format: &str, base: Self, custom_formats: &CustomFormats) -> Self {
+        Self::Custom {
+            format: format.to_owned(),
+            base: Arc::new(base),
+            typenames: custom_formats[format].clone(),
+        }
+    }
+
+    /// Name for a variant of this type in an untagged enum.
+    fn untagged_variant_name(&self) -> String {
+        match self {
+            Self::SchemaRef { name } => name.clone(),
+            Self::List { inner } => format!("{}List", inner.untagged_variant_name()),
+            Self::Set { inner } => format!("{}Set", inner.untagged_variant_name()),
+            Self::Map { value_ty } => format!("{}Map", value_ty.untagged_variant_name()),
+            Self::StringConst { value } => value.to_upper_camel_case(),
+            Self::Custom { format, .. } => format.to_upper_camel_case(),
+            Self::Bool => "Bool".to_owned(),
+            Self::Int16 => "Int16".to_owned(),
+            Self::UInt16 => "UInt16".to_owned(),
+            Self::Int32 => "Int32".to_owned(),
+            Self::Int64 => "Int64".to_owned(),
+            Self::UInt64 => "UInt64".to_owned(),
+            Self::Float32 => "Float32".to_owned(),
+            Self::Float64 => "Float64".to_owned(),
+            Self::Decimal => "Decimal".to_owned(),
+            Self::String => "String".to_owned(),
+            Self::DateTime => "DateTime".to_owned(),
+            Self::Date => "Date".to_owned(),
+            Self::Time => "Time".to_owned(),
+            Self::Duration => "Duration".to_owned(),
+            Self::Uri => "Uri".to_owned(),
+            Self::Uuid => "Uuid".to_owned(),
+            Self::Email => "Email".to_owned(),
+            Self::Ipv4 => "Ipv4".to_owned(),
+            Self::Ipv6 => "Ipv6".to_owned(),
+            Self::Base64 => "Base64".to_owned(),
+            Self::Binary => "Binary".to_owned(),
+            Self::JsonObject => "JsonObject".to_owned(),
+        }
+    }
+
     fn to_csharp_typename(&self) -> Cow<'_, str> {
         match self {
+            Self::Custom {
+                base, typenames, ..
+            } => match typenames.get("csharp") {
+                Some(typename) => typename.as_str().into(),
+                None => base.to_csharp_typename(),
+            },
             Self::Bool => "bool".into(),
             Self::Int16 => "short".into(),
             Self::Int32 => "int".into(),
             Self::Int64 => "long".into(),
             Self::UInt16 => "ushort".into(),
             Self::UInt64 => "ulong".into(),
+            Self::Float32 => "float".into(),
+            Self::Float64 => "double".into(),
+            Self::Decimal => "decimal".into(),
             Self::String => "string".into(),
             Self::DateTime => "DateTime".into(),
-            Self::Uri => "string".into(),
+            Self::Date => "DateOnly".into(),
+            Self::Time => "TimeOnly".into(),
+            Self::Uuid => "Guid".into(),
+            // Newtonsoft.Json doesn't use the ISO 8601 format for TimeSpan
+            Self::Duration | Self::Uri | Self::Email | Self::Ipv4 | Self::Ipv6 => "string".into(),
+            Self::Base64 | Self::Binary => "byte[]".into(),
             Self::JsonObject => "Object".into(),
             Self::Map { value_ty } => {
                 format!("Dictionary<string, {}>", value_ty.to_csharp_typename()).into()
@@ -550,60 +1221,116 @@ impl FieldType {
             Self::List { inner } | Self::Set { inner } => {
                 format!("List<{}>", inner.to_csharp_typename()).into()
             }
-            Self::SchemaRef { name } => filter_schema_ref(name, "Object"),
+            Self::SchemaRef { name } => name.clone().into(),
             Self::StringConst { .. } => "string".into(),
         }
     }
 
     fn to_go_typename(&self) -> Cow<'_, str> {
         match self {
+            Self::Custom {
+                base, typenames, ..
+            } => match typenames.get("go") {
+                Some(typename) => typename.as_str().into(),
+                None => base.to_go_typename(),
+            },
             Self::Bool => "bool".into(),
             Self::Int16 => "int16".into(),
             Self::Int32 => "int32".into(),
             Self::Int64 => "int64".into(),
             Self::UInt16 => "uint16".into(),
             Self::UInt64 => "uint64".into(),
-            Self::Uri | Self::String => "string".into(),
+            Self::Float32 => "float32".into(),
+            Self::Float64 | Self::Decimal => "float64".into(),
+            Self::Uri
+            | Self::String
+            | Self::Date
+            | Self::Time
+            | Self::Duration
+            | Self::Uuid
+            | Self::Email
+            | Self::Ipv4
+            | Self::Ipv6 => "string".into(),
             Self::DateTime => "time.Time".into(),
+            // encoding/json (un)marshals byte slices as base64
+            Self::Base64 | Self::Binary => "[]byte".into(),
             Self::JsonObject => "map[string]any".into(),
             Self::Map { value_ty } => format!("map[string]{}", value_ty.to_go_typename()).into(),
             Self::List { inner } | Self::Set { inner } => {
                 format!("[]{}", inner.to_go_typename()).into()
             }
-            Self::SchemaRef { name } => filter_schema_ref(name, "map[string]any"),
+            Self::SchemaRef { name } => name.clone().into(),
             Self::StringConst { .. } => "string".into(),
         }
     }
 
     fn to_kotlin_typename(&self) -> Cow<'_, str> {
         match self {
+            Self::Custom {
+                base, typenames, ..
+            } => match typenames.get("kotlin") {
+                Some(typename) => typename.as_str().into(),
+                None => base.to_kotlin_typename(),
+            },
             Self::Bool => "Boolean".into(),
             Self::Int16 => "Short".into(),
             Self::Int32 => "Int".into(),
             Self::UInt16 => "UShort".into(),
             Self::Int64 => "Long".into(),
             Self::UInt64 => "ULong".into(),
-            Self::Uri | Self::String => "String".into(),
+            Self::Float32 => "Float".into(),
+            // kotlinx.serialization has no built-in serializer for BigDecimal
+            Self::Float64 | Self::Decimal => "Double".into(),
+            Self::Uri | Self::String | Self::Uuid | Self::Email | Self::Ipv4 | Self::Ipv6 => {
+                "String".into()
+            }
             Self::DateTime => "Instant".into(),
+            Self::Date => "LocalDate".into(),
+            Self::Time => "LocalTime".into(),
+            Self::Duration => "Duration".into(),
+            // kotlinx.serialization encodes ByteArray as a list of numbers, not base64
+            Self::Base64 => "String".into(),
+            Self::Binary => "ByteArray".into(),
             Self::Map { value_ty } => {
                 format!("Map<String,{}>", value_ty.to_kotlin_typename()).into()
             }
             Self::JsonObject => "Map<String,Any>".into(),
             Self::List { inner } => format!("List<{}>", inner.to_kotlin_typename()).into(),
             Self::Set { inner } => format!("Set<{}>", inner.to_kotlin_typename()).into(),
-            Self::SchemaRef { name } => filter_schema_ref(name, "Map<String,Any>"),
+            Self::SchemaRef { name } => name.clone().into(),
             Self::StringConst { .. } => "String".into(),
         }
     }
 
     fn to_js_typename(&self) -> Cow<'_, str> {
         match self {
+            Self::Custom {
+                base, typenames, ..
+            } => match typenames.get("js") {
+                Some(typename) => typename.as_str().into(),
+                None => base.to_js_typename(),
+            },
             Self::Bool => "boolean".into(),
-            Self::Int16 | Self::UInt16 | Self::Int32 | Self::Int64 | Self::UInt64 => {
-                "number".into()
-            }
-            Self::String | Self::Uri => "string".into(),
+            Self::Int16
+            | Self::UInt16
+            | Self::Int32
+            | Self::Int64
+            | Self::UInt64
+            | Self::Float32
+            | Self::Float64
+            | Self::Decimal => "number".into(),
+            Self::String
+            | Self::Uri
+            | Self::Date
+            | Self::Time
+            | Self::Duration
+            | Self::Uuid
+            | Self::Email
+            | Self::Ipv4
+            | Self::Ipv6
+            | Self::Base64 => "string".into(),
             Self::DateTime => "Date".into(),
+            Self::Binary => "Blob".into(),
             Self::JsonObject => "any".into(),
             Self::List { inner } | Self::Set { inner } => {
                 format!("{}[]", inner.to_js_typename()).into()
@@ -611,46 +1338,118 @@ impl FieldType {
             Self::Map { value_ty } => {
                 format!("{{ [key: string]: {} }}", value_ty.to_js_typename()).into()
             }
-            Self::SchemaRef { name } => filter_schema_ref(name, "any"),
+            Self::SchemaRef { name } => name.clone().into(),
             Self::StringConst { .. } => "string".into(),
         }
     }
 
-    fn to_rust_typename(&self) -> Cow<'_, str> {
+    fn to_rust_typename(&self, set_type: RustSetType) -> Cow<'_, str> {
         match self {
+            Self::Custom {
+                base, typenames, ..
+            } => match typenames.get("rust") {
+                Some(typename) => typename.as_str().into(),
+                None => base.to_rust_typename(set_type),
+            },
             Self::Bool => "bool".into(),
             Self::Int16 => "i16".into(),
             Self::UInt16 => "u16".into(),
-            Self::Int32 |
-            // FIXME: All integers in query params are currently i32
-            Self::Int64 | Self::UInt64 => "i32".into(),
+            Self::Int32 => "i32".into(),
+            Self::Int64 => "i64".into(),
+            Self::UInt64 => "u64".into(),
+            Self::Float32 => "f32".into(),
+            // FIXME: Use a decimal type for Decimal?
+            Self::Float64 | Self::Decimal => "f64".into(),
             // FIXME: Do we want a separate type for Uri?
-            Self::Uri | Self::String => "String".into(),
-            // FIXME: Depends on those chrono imports being in scope, not that great..
-            Self::DateTime => "DateTime<Utc>".into(),
+            Self::Uri | Self::String | Self::Email | Self::Duration | Self::Base64 => {
+                "String".into()
+            }
+            Self::DateTime => "chrono::DateTime<chrono::Utc>".into(),
+            Self::Date => "chrono::NaiveDate".into(),
+            Self::Time => "chrono::NaiveTime".into(),
+            Self::Uuid => "uuid::Uuid".into(),
+            Self::Ipv4 => "std::net::Ipv4Addr".into(),
+            Self::Ipv6 => "std::net::Ipv6Addr".into(),
+            Self::Binary => "Vec<u8>".into(),
             Self::JsonObject => "serde_json::Value".into(),
-            // FIXME: Treat set differently? (BTreeSet)
-            Self::List { inner } | Self::Set { inner } => {
-                format!("Vec<{}>", inner.to_rust_typename()).into()
+            Self::List { inner } => format!("Vec<{}>", inner.to_rust_typename(set_type)).into(),
+            Self::Set { inner } => {
+                let set = match set_type {
+                    RustSetType::BTreeSet => "std::collections::BTreeSet",
+                    RustSetType::HashSet => "std::collections::HashSet",
+                    RustSetType::Vec => "Vec",
+                };
+                format!("{set}<{}>", inner.to_rust_typename(set_type)).into()
             }
             Self::Map { value_ty } => format!(
                 "std::collections::HashMap<String, {}>",
-                value_ty.to_rust_typename(),
+                value_ty.to_rust_typename(set_type),
             )
             .into(),
-            Self::SchemaRef { name } => filter_schema_ref(name, "serde_json::Value"),
-            Self::StringConst { .. } => "String".into()
+            Self::SchemaRef { name } => name.clone().into(),
+            Self::StringConst { .. } => "String".into(),
         }
     }
 
-    pub(crate) fn referenced_schema(&self) -> Option<&str> {
+    /// Imports that need to be in scope for this type's name in the given language.
+    ///
+    /// Imports are fully qualified, in the notation of the language (e.g. `java.util.UUID` or
+    /// `datetime.date`). Rust type names are fully qualified themselves, so they need no imports.
+    pub(crate) fn required_imports(&self, lang: &str) -> BTreeSet<&'static str> {
+        let mut imports = match self {
+            Self::List { inner } | Self::Set { inner } => inner.required_imports(lang),
+            Self::Map { value_ty } => value_ty.required_imports(lang),
+            // the base type is only used if there's no explicit type name
+            Self::Custom {
+                base, typenames, ..
+            } if !typenames.contains_key(lang) => return base.required_imports(lang),
+            _ => BTreeSet::new(),
+        };
+
+        let own: &[&'static str] = match (lang, self) {
+            ("go", Self::DateTime) => &["time"],
+            ("java", Self::DateTime) => &["java.time.OffsetDateTime"],
+            ("java", Self::Date) => &["java.time.LocalDate"],
+            ("java", Self::Time) => &["java.time.LocalTime"],
+            ("java", Self::Duration) => &["java.time.Duration"],
+            ("java", Self::Uri) => &["java.net.URI"],
+            ("java", Self::Uuid) => &["java.util.UUID"],
+            ("java", Self::Decimal) => &["java.math.BigDecimal"],
+            ("java", Self::List { .. }) => &["java.util.List"],
+            ("java", Self::Set { .. }) => &["java.util.Set"],
+            ("java", Self::Map { .. }) => &["java.util.Map"],
+            ("kotlin", Self::DateTime) => &["kotlinx.datetime.Instant"],
+            ("kotlin", Self::Date) => &["kotlinx.datetime.LocalDate"],
+            ("kotlin", Self::Time) => &["kotlinx.datetime.LocalTime"],
+            ("kotlin", Self::Duration) => &["kotlin.time.Duration"],
+            ("python", Self::DateTime) => &["datetime.datetime"],
+            ("python", Self::Date) => &["datetime.date"],
+            ("python", Self::Time) => &["datetime.time"],
+            ("python", Self::Duration) => &["datetime.timedelta"],
+            ("python", Self::Decimal) => &["decimal.Decimal"],
+            ("python", Self::Uuid) => &["uuid.UUID"],
+            ("python", Self::Ipv4) => &["ipaddress.IPv4Address"],
+            ("python", Self::Ipv6) => &["ipaddress.IPv6Address"],
+            _ => &[],
+        };
+        imports.extend(own);
+        imports
+    }
+
+    /// Replace references to any of the schemas `names` with [`FieldType::JsonObject`].
+    fn replace_refs_with_json_object(&mut self, names: &BTreeSet<String>) {
         match self {
-            Self::SchemaRef { name } => {
-                // TODO(10055): the `BackgroundTaskFinishedEvent2` struct has a field with type of `Data`
-                // this corresponds to a `#[serde(untagged)]` enum `svix_server::v1::endpoints::background_tasks::Data`
-                // we should change this server side, but for now I am changing it here
-                if name == "Data" { None } else { Some(name) }
+            Self::SchemaRef { name } if names.contains(name) => *self = Self::JsonObject,
+            Self::List { inner } | Self::Set { inner } | Self::Map { value_ty: inner } => {
+                Arc::make_mut(inner).replace_refs_with_json_object(names);
             }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn referenced_schema(&self) -> Option<&str> {
+        match self {
+            Self::SchemaRef { name } => Some(name),
             Self::List { inner: ty } | Self::Set { inner: ty } | Self::Map { value_ty: ty } => {
                 ty.referenced_schema()
             }
@@ -660,12 +1459,27 @@ impl FieldType {
 
     fn to_python_typename(&self) -> Cow<'_, str> {
         match self {
+            Self::Custom {
+                base, typenames, ..
+            } => match typenames.get("python") {
+                Some(typename) => typename.as_str().into(),
+                None => base.to_python_typename(),
+            },
             Self::Bool => "bool".into(),
             Self::Int16 | Self::UInt16 | Self::Int32 | Self::Int64 | Self::UInt64 => "int".into(),
+            Self::Float32 | Self::Float64 => "float".into(),
+            Self::Decimal => "Decimal".into(),
             Self::String => "str".into(),
             Self::DateTime => "datetime".into(),
-            Self::SchemaRef { name } => filter_schema_ref(name, "t.Dict[str, t.Any]"),
-            Self::Uri => "str".into(),
+            Self::Date => "date".into(),
+            Self::Time => "time".into(),
+            Self::Duration => "timedelta".into(),
+            Self::Uuid => "UUID".into(),
+            Self::Ipv4 => "IPv4Address".into(),
+            Self::Ipv6 => "IPv6Address".into(),
+            Self::Binary => "bytes".into(),
+            Self::SchemaRef { name } => name.clone().into(),
+            Self::Uri | Self::Email | Self::Base64 => "str".into(),
             Self::JsonObject => "t.Dict[str, t.Any]".into(),
             Self::Set { inner } | Self::List { inner } => {
                 format!("t.List[{}]", inner.to_python_typename()).into()
@@ -679,14 +1493,30 @@ impl FieldType {
 
     fn to_java_typename(&self) -> Cow<'_, str> {
         match self {
+            Self::Custom {
+                base, typenames, ..
+            } => match typenames.get("java") {
+                Some(typename) => typename.as_str().into(),
+                None => base.to_java_typename(),
+            },
             // _ => "String".into(),
             FieldType::Bool => "Boolean".into(),
             FieldType::Int16 => "Short".into(),
             FieldType::UInt16 | FieldType::UInt64 | FieldType::Int64 => "Long".into(),
             FieldType::Int32 => "Integer".into(),
+            FieldType::Float32 => "Float".into(),
+            FieldType::Float64 => "Double".into(),
+            FieldType::Decimal => "BigDecimal".into(),
             FieldType::String => "String".into(),
             FieldType::DateTime => "OffsetDateTime".into(),
+            FieldType::Date => "LocalDate".into(),
+            FieldType::Time => "LocalTime".into(),
+            FieldType::Duration => "Duration".into(),
             FieldType::Uri => "URI".into(),
+            FieldType::Uuid => "UUID".into(),
+            FieldType::Email | FieldType::Ipv4 | FieldType::Ipv6 => "String".into(),
+            // Jackson (de)serializes byte arrays as base64
+            FieldType::Base64 | FieldType::Binary => "byte[]".into(),
             FieldType::JsonObject => "Object".into(),
             FieldType::List { inner } => format!("List<{}>", inner.to_java_typename()).into(),
             FieldType::Set { inner: field_type } => {
@@ -695,19 +1525,51 @@ impl FieldType {
             FieldType::Map { value_ty } => {
                 format!("Map<String,{}>", value_ty.to_java_typename()).into()
             }
-            FieldType::SchemaRef { name } => filter_schema_ref(name, "Object"),
+            FieldType::SchemaRef { name } => name.clone().into(),
             // backwards compat
             FieldType::StringConst { .. } => "TypeEnum".into(),
         }
     }
 
+    /// Type name in RBS signatures, matching the values held by the Ruby library's models.
     fn to_ruby_typename(&self) -> Cow<'_, str> {
         match self {
-            FieldType::SchemaRef { name } => name.clone().into(),
-            FieldType::StringConst { .. } => {
-                unreachable!("FieldType::const should never be exposed to template code")
+            Self::Custom {
+                base, typenames, ..
+            } => match typenames.get("ruby") {
+                Some(typename) => typename.as_str().into(),
+                None => base.to_ruby_typename(),
+            },
+            Self::Bool => "bool".into(),
+            Self::Int16 | Self::UInt16 | Self::Int32 | Self::Int64 | Self::UInt64 => {
+                "Integer".into()
             }
-            _ => panic!("types? in ruby?!?!, not on my watch!"),
+            // JSON.parse returns floats for all non-integer numbers
+            Self::Float32 | Self::Float64 | Self::Decimal => "Float".into(),
+            // the models only convert date-times when deserializing, everything else stays as-is
+            Self::DateTime => "Time".into(),
+            Self::String
+            | Self::Date
+            | Self::Time
+            | Self::Duration
+            | Self::Uri
+            | Self::Uuid
+            | Self::Email
+            | Self::Ipv4
+            | Self::Ipv6
+            | Self::Base64
+            | Self::Binary
+            | Self::StringConst { .. } => "String".into(),
+            // map values are never converted
+            Self::JsonObject | Self::Map { .. } => "Hash[String, untyped]".into(),
+            // the models only convert list items that are models themselves
+            Self::List { inner } | Self::Set { inner } => match &**inner {
+                Self::SchemaRef { name } => format!("Array[{name}]").into(),
+                Self::DateTime => "Array[String]".into(),
+                Self::List { .. } | Self::Set { .. } | Self::Map { .. } => "Array[untyped]".into(),
+                inner => format!("Array[{}]", inner.to_ruby_typename()).into(),
+            },
+            Self::SchemaRef { name } => name.clone().into(),
         }
     }
 }
@@ -719,7 +1581,7 @@ impl minijinja::value::Object for FieldType {
 
     fn call_method(
         self: &Arc<Self>,
-        _state: &minijinja::State<'_, '_>,
+        state: &minijinja::State<'_, '_>,
         method: &str,
         args: &[minijinja::Value],
     ) -> Result<minijinja::Value, minijinja::Error> {
@@ -746,7 +1608,11 @@ impl minijinja::value::Object for FieldType {
             }
             "to_rust" => {
                 ensure_no_args(args, "to_rust")?;
-                Ok(self.to_rust_typename().into())
+                let set_type = state
+                    .lookup(RUST_CONFIG_GLOBAL)
+                    .and_then(|c| c.downcast_object_ref::<RustConfig>().map(|c| c.set_type))
+                    .unwrap_or_default();
+                Ok(self.to_rust_typename(set_type).into())
             }
             "to_java" => {
                 ensure_no_args(args, "to_java")?;
@@ -756,6 +1622,17 @@ impl minijinja::value::Object for FieldType {
                 ensure_no_args(args, "to_ruby")?;
                 Ok(self.to_ruby_typename().into())
             }
+            "required_imports" => {
+                let (lang,): (&str,) = from_args(args)?;
+                Ok(minijinja::Value::from_iter(self.required_imports(lang)))
+            }
+            // Type name from the template set's types.toml
+            "to_lang" => {
+                let (lang, kwargs): (&str, Kwargs) = from_args(args)?;
+                let nullable = kwargs.get::<Option<bool>>("nullable")?.unwrap_or(false);
+                kwargs.assert_all_used()?;
+                Ok(type_mapping::to_lang(state, self, lang, nullable)?.into())
+            }
 
             "is_datetime" => {
                 ensure_no_args(args, "is_datetime")?;
@@ -854,13 +1731,6 @@ where
     }
 }
 
-fn filter_schema_ref<'a>(name: &'a String, json_obj_typename: &'a str) -> Cow<'a, str> {
-    // TODO(10055): the `BackgroundTaskFinishedEvent2` struct has a field with type of `Data`
-    // this corresponds to a `#[serde(untagged)]` enum `svix_server::v1::endpoints::background_tasks::Data`
-    // we should change this server side, but for now I am changing it here
-    if name == "Data" {
-        json_obj_typename.into()
-    } else {
-        name.clone().into()
-    }
+fn is_inline_object_schema(s: &Schema) -> bool {
+    matches!(s, Schema::Object(o) if o.object.as_ref().is_some_and(|o| !o.properties.is_empty()))
 }
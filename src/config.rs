@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+use camino::Utf8Path;
+use fs_err as fs;
+use serde::Deserialize;
+
+/// Codegen configuration, loaded from the file passed to `--config`.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Custom `format`s for string, integer and number schemas.
+    #[serde(default)]
+    pub formats: CustomFormats,
+    /// Options for Rust type names.
+    #[serde(default)]
+    pub rust: RustConfig,
+}
+
+impl Config {
+    pub(crate) fn load(path: &Utf8Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).with_context(|| format!("failed to parse config `{path}`"))
+    }
+}
+
+/// Name of the template global holding the [`RustConfig`].
+pub(crate) const RUST_CONFIG_GLOBAL: &str = "__rust_config";
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RustConfig {
+    /// Collection type to use for lists with unique items.
+    #[serde(default)]
+    pub set_type: RustSetType,
+}
+
+impl minijinja::value::Object for RustConfig {}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RustSetType {
+    BTreeSet,
+    HashSet,
+    /// Keep using `Vec`, like for lists without unique items, so item types don't have to
+    /// implement `Ord` or `Hash`.
+    #[default]
+    Vec,
+}
+
+/// Custom formats by name.
+///
+/// Each format maps template languages (the suffix of the type's `to_*` method, e.g. `rust` or
+/// `js`) to the name of the type to use for it, for example:
+///
+/// ```toml
+/// [formats.svix-id]
+/// rust = "svix::Id"
+/// go = "string"
+/// js = "string"
+/// ```
+///
+/// Languages without an entry use the type of the schema without its format.
+pub(crate) type CustomFormats = BTreeMap<String, BTreeMap<String, String>>;
@@ -4,13 +4,15 @@ use anyhow::{Context as _, bail};
 use camino::Utf8Path;
 use fs_err::{self as fs, File};
 use heck::{ToLowerCamelCase, ToSnakeCase as _, ToUpperCamelCase as _};
-use minijinja::{Template, context};
+use minijinja::{Template, Value, context};
 use serde::Deserialize;
 
 use crate::{
     api::{Api, Resource},
+    config::{RUST_CONFIG_GLOBAL, RustConfig},
     postprocessing::Postprocessor,
     template,
+    type_mapping::{TYPE_MAPPINGS_GLOBAL, TypeMappings},
 };
 
 #[derive(Default, Deserialize)]
@@ -28,6 +30,8 @@ pub(crate) fn generate(
     tpl_name: String,
     output_dir: &Utf8Path,
     no_postprocess: bool,
+    split_versions: bool,
+    rust_config: RustConfig,
 ) -> anyhow::Result<()> {
     let (name_without_jinja_suffix, tpl_path) = match tpl_name.strip_suffix(".jinja") {
         Some(basename) => (basename, &tpl_name),
@@ -53,11 +57,14 @@ pub(crate) fn generate(
 
     let tpl_source = fs::read_to_string(tpl_path)?;
 
-    let mut minijinja_env = template::env(
-        Utf8Path::new(tpl_path)
-            .parent()
-            .with_context(|| format!("invalid template path `{tpl_path}`"))?,
-    )?;
+    let tpl_dir = Utf8Path::new(tpl_path)
+        .parent()
+        .with_context(|| format!("invalid template path `{tpl_path}`"))?;
+    let mut minijinja_env = template::env(tpl_dir)?;
+    if let Some(type_mappings) = TypeMappings::load(tpl_dir)? {
+        minijinja_env.add_global(TYPE_MAPPINGS_GLOBAL, Value::from_object(type_mappings));
+    }
+    minijinja_env.add_global(RUST_CONFIG_GLOBAL, Value::from_object(rust_config));
     minijinja_env.add_template(tpl_path, &tpl_source)?;
     let tpl = minijinja_env.get_template(tpl_path)?;
 
@@ -71,6 +78,7 @@ pub(crate) fn generate(
         output_dir,
         postprocessor: &postprocessor,
         no_postprocess,
+        split_versions,
     };
 
     match tpl_kind {
@@ -93,6 +101,8 @@ struct Generator<'a> {
     output_dir: &'a Utf8Path,
     postprocessor: &'a Postprocessor,
     no_postprocess: bool,
+    /// Whether to write API resources to a subdirectory named after their version.
+    split_versions: bool,
 }
 
 impl Generator<'_> {
@@ -108,9 +118,11 @@ impl Generator<'_> {
             let referenced_components = resource.referenced_components();
             for operation in &resource.operations {
                 if operation.has_query_or_header_params() {
+                    let required_imports = operation.required_imports(self.lang());
                     self.render_tpl(
+                        self.version_subdir(resource),
                         Some(&format!("{}_{}_Options", resource.name, operation.name)),
-                        context! { operation, resource, referenced_components },
+                        context! { operation, resource, referenced_components, required_imports },
                     )?;
                 }
             }
@@ -131,9 +143,11 @@ impl Generator<'_> {
     ) -> anyhow::Result<()> {
         for resource in resources {
             let referenced_components = resource.referenced_components();
+            let required_imports = resource.required_imports(self.lang());
             self.render_tpl(
+                self.version_subdir(resource),
                 Some(&resource.name),
-                context! { resource, referenced_components },
+                context! { resource, referenced_components, required_imports },
             )?;
             self.generate_api_resources_inner(resource.subresources.values())?;
         }
@@ -145,9 +159,11 @@ impl Generator<'_> {
         let output_dir = output_dir.as_str();
         for (name, ty) in api.types {
             let referenced_components = ty.referenced_components();
+            let required_imports = ty.required_imports(self.lang());
             self.render_tpl(
+                None,
                 Some(&name),
-                context! { type => ty, referenced_components, output_dir },
+                context! { type => ty, referenced_components, required_imports, output_dir },
             )?;
         }
 
@@ -155,10 +171,38 @@ impl Generator<'_> {
     }
 
     fn generate_summary(&self, api: Api) -> anyhow::Result<()> {
-        self.render_tpl(None, context! { api })
+        // Tells templates whether API resources are in per-version subdirectories
+        let split_versions = self.split_versions;
+        self.render_tpl(None, None, context! { api, split_versions })
+    }
+
+    /// Language name used for type names and imports, as in `to_<lang>` / `required_imports`.
+    fn lang(&self) -> &str {
+        match self.tpl_file_ext {
+            "cs" => "csharp",
+            "kt" => "kotlin",
+            "py" => "python",
+            "rb" | "rbs" => "ruby",
+            "rs" => "rust",
+            "ts" => "js",
+            ext => ext,
+        }
     }
 
-    fn render_tpl(&self, output_name: Option<&str>, ctx: minijinja::Value) -> anyhow::Result<()> {
+    fn version_subdir<'r>(&self, resource: &'r Resource) -> Option<&'r str> {
+        if self.split_versions {
+            resource.version.as_deref()
+        } else {
+            None
+        }
+    }
+
+    fn render_tpl(
+        &self,
+        subdir: Option<&str>,
+        output_name: Option<&str>,
+        ctx: minijinja::Value,
+    ) -> anyhow::Result<()> {
         let tpl_file_ext = self.tpl_file_ext;
         let basename = match (output_name, tpl_file_ext) {
             (Some(name), "ts") => name.to_lower_camel_case(),
@@ -169,11 +213,19 @@ impl Generator<'_> {
             (None, "cs" | "java" | "kt") => "Summary".to_owned(),
             (None, "ts") => "index".to_owned(),
             (None, "go") => "models".to_owned(),
-            (None, "rb") => "svix".to_owned(),
+            (None, "rb" | "rbs") => "svix".to_owned(),
             (None, _) => "summary".to_owned(),
         };
 
-        let file_path = self.output_dir.join(format!("{basename}.{tpl_file_ext}"));
+        let output_dir = match subdir {
+            Some(subdir) => {
+                let dir = self.output_dir.join(subdir);
+                fs::create_dir_all(&dir)?;
+                dir
+            }
+            None => self.output_dir.to_owned(),
+        };
+        let file_path = output_dir.join(format!("{basename}.{tpl_file_ext}"));
 
         let out_file = BufWriter::new(File::create(&file_path)?);
 
@@ -12,11 +12,17 @@ use fs_err::{self as fs};
 use tempfile::TempDir;
 
 mod api;
+mod config;
 mod generator;
 mod postprocessing;
 mod template;
+mod type_mapping;
 
-use self::{api::Api, generator::generate};
+use self::{
+    api::{Api, OperationFilter},
+    config::Config,
+    generator::generate,
+};
 
 #[derive(Parser)]
 struct CliArgs {
@@ -37,6 +43,51 @@ struct CliArgs {
     #[arg(global = true, long = "include-op-id")]
     specified_operations: Vec<String>,
 
+    /// Which API version to generate code for, e.g. `v2`, or `all`.
+    ///
+    /// With `all`, API resources of each version are written to their own subdirectory of the
+    /// output directory. Operations without a version are always included.
+    ///
+    /// Summary templates have to be version-aware to work with `all`: they get `split_versions` to
+    /// tell whether resources are in per-version subdirectories. Of the bundled templates, only
+    /// the Rust one is.
+    #[arg(global = true, long, default_value = "v1")]
+    api_version: String,
+
+    /// Include operations with `multipart/form-data`, `application/octet-stream` or `text/plain`
+    /// request or success response bodies.
+    ///
+    /// Only use this with templates that look at the operations' body content types, none of the
+    /// bundled ones do yet.
+    #[arg(global = true, long)]
+    non_json_bodies: bool,
+
+    /// Include operations whose success responses have different bodies, e.g. `200` with one type
+    /// and `202` with another.
+    ///
+    /// Only use this with templates that look at the operations' `success_responses`, none of the
+    /// bundled ones do yet.
+    #[arg(global = true, long)]
+    distinct_success_bodies: bool,
+
+    /// Keep `oneOf` / `anyOf` schemas without discriminator as `untagged_enum` types.
+    ///
+    /// Otherwise, fields referencing them use the JSON object type. Only use this with templates
+    /// that handle the `untagged_enum` type kind, none of the bundled ones do yet.
+    #[arg(global = true, long)]
+    untagged_enums: bool,
+
+    /// How to group operations into resources.
+    ///
+    /// With any strategy but `operation-id`, operations without an operation ID are named after
+    /// their HTTP method, e.g. `list`, `get` or `create`.
+    #[arg(global = true, long, value_enum, default_value_t = ResourceGrouping::OperationId)]
+    resource_grouping: ResourceGrouping,
+
+    /// Path to a TOML config file, e.g. to declare custom `format`s and their type names.
+    #[arg(global = true, long)]
+    config: Option<Utf8PathBuf>,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -82,13 +133,40 @@ enum IncludeMode {
     OnlySpecified,
 }
 
+#[derive(Copy, Clone, clap::ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+enum ResourceGrouping {
+    /// Operation IDs of the form `v1.resource.sub_resource.operation`
+    OperationId,
+    /// The operation's first tag, with the operation ID as the operation name
+    Tag,
+    /// The static segments of the operation's path (minus `api` and version segments, in
+    /// snake_case), with the operation ID as the operation name
+    Path,
+    /// The operation's `x-codegen-resource` (a period-separated resource path) and
+    /// `x-codegen-name` (defaulting to the operation ID) extensions
+    Extension,
+}
+
 fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt().with_writer(io::stderr).init();
 
     let args = CliArgs::parse();
 
-    let excluded_operations = BTreeSet::from_iter(args.excluded_operations);
-    let specified_operations = BTreeSet::from_iter(args.specified_operations);
+    let filter = OperationFilter {
+        include_mode: args.include_mode,
+        excluded_operations: BTreeSet::from_iter(args.excluded_operations),
+        specified_operations: BTreeSet::from_iter(args.specified_operations),
+        api_version: (args.api_version != "all").then_some(args.api_version),
+        non_json_bodies: args.non_json_bodies,
+        distinct_success_bodies: args.distinct_success_bodies,
+    };
+    let split_versions = filter.api_version.is_none();
+
+    let config = match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
 
     let input_files = match &args.command {
         Command::Generate { input_file, .. } => input_file,
@@ -104,25 +182,30 @@ fn main() -> anyhow::Result<()> {
                 .context("input file must have a file extension")?;
             let input_file_contents = fs::read_to_string(input_file)?;
 
-            if input_file_ext == "json" {
-                let spec: OpenApi = serde_json::from_str(&input_file_contents)
-                    .context("failed to parse OpenAPI spec")?;
-
-                let webhooks = get_webhooks(&spec);
-                Api::new(
-                    spec.paths.context("found no endpoints in input spec")?,
-                    &mut spec.components.unwrap_or_default(),
-                    &webhooks,
-                    args.include_mode,
-                    &excluded_operations,
-                    &specified_operations,
-                )
-                .context("converting OpenAPI spec to our own representation")
+            let spec: OpenApi = if input_file_ext == "json" {
+                serde_json::from_str(&input_file_contents)
+                    .context("failed to parse OpenAPI spec")?
+            } else if input_file_ext == "yaml" || input_file_ext == "yml" {
+                // serde_norway errors include the line and column of the problem
+                serde_norway::from_str(&input_file_contents)
+                    .context("failed to parse OpenAPI spec")?
             } else if input_file_ext == "ron" {
-                ron::from_str(&input_file_contents).context("parsing ron file")
+                return ron::from_str(&input_file_contents).context("parsing ron file");
             } else {
-                bail!("input file extension must be .json or .ron");
-            }
+                bail!("input file extension must be .json, .yaml, .yml or .ron");
+            };
+
+            let webhooks = get_webhooks(&spec);
+            Api::new(
+                spec.paths.context("found no endpoints in input spec")?,
+                &mut spec.components.unwrap_or_default(),
+                &webhooks,
+                &filter,
+                args.resource_grouping,
+                &config.formats,
+                args.untagged_enums,
+            )
+            .context("converting OpenAPI spec to our own representation")
         })
         .collect::<anyhow::Result<Api>>()?;
 
@@ -135,7 +218,14 @@ fn main() -> anyhow::Result<()> {
         } => {
             match &output_dir {
                 Some(path) => {
-                    generate(api, template.into(), path, no_postprocess)?;
+                    generate(
+                        api,
+                        template.into(),
+                        path,
+                        no_postprocess,
+                        split_versions,
+                        config.rust,
+                    )?;
                     println!("done! output written to {path}");
                 }
                 None => {
@@ -160,7 +250,14 @@ fn main() -> anyhow::Result<()> {
                         .try_into()
                         .context("non-UTF8 tempdir path")?;
 
-                    generate(api, template.into(), path, no_postprocess)?;
+                    generate(
+                        api,
+                        template.into(),
+                        path,
+                        no_postprocess,
+                        split_versions,
+                        config.rust,
+                    )?;
                     println!("done! output written to {path}");
 
                     // Persist the TempDir if everything was successful
@@ -28,6 +28,8 @@ impl Postprocessor {
             "java" => PostprocessorLanguage::Java,
             "ts" => PostprocessorLanguage::TypeScript,
             "rb" => PostprocessorLanguage::Ruby,
+            // there's no established formatter for RBS signatures
+            "rbs" => PostprocessorLanguage::Unknown,
             _ => {
                 tracing::warn!("no known postprocessing command(s) for {ext} files");
                 PostprocessorLanguage::Unknown
@@ -0,0 +1,199 @@
+//! Data-driven type names for template languages without built-in support.
+//!
+//! A template set can provide a `types.toml` next to its templates, with one table per language:
+//!
+//! ```toml
+//! [swift]
+//! bool = "Bool"
+//! integer = "Int"
+//! number = "Double"
+//! string = "String"
+//! date_time = "Date"
+//! json_object = "[String: AnyCodable]"
+//! list = "[{inner}]"
+//! set = "Set<{inner}>"
+//! map = "[String: {value}]"
+//! schema_ref = "{name}"
+//! nullable = "{inner}?"
+//! ```
+//!
+//! Templates then use `ty.to_lang("swift")` (or `ty.to_lang("swift", nullable=true)`).
+
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+use camino::Utf8Path;
+use fs_err as fs;
+use minijinja::{ErrorKind, State};
+use serde::Deserialize;
+
+use crate::api::FieldType;
+
+/// Name of the template global holding the loaded [`TypeMappings`].
+pub(crate) const TYPE_MAPPINGS_GLOBAL: &str = "__type_mappings";
+
+/// Type mappings by language name.
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct TypeMappings(BTreeMap<String, LanguageTypeMapping>);
+
+impl TypeMappings {
+    /// Load `types.toml` from the template directory, if it exists.
+    pub(crate) fn load(tpl_dir: &Utf8Path) -> anyhow::Result<Option<Self>> {
+        let path = tpl_dir.join("types.toml");
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        let mappings =
+            toml::from_str(&contents).with_context(|| format!("failed to parse `{path}`"))?;
+        Ok(Some(mappings))
+    }
+}
+
+impl minijinja::value::Object for TypeMappings {}
+
+/// Type name patterns for one language.
+///
+/// `integer`, `number` and `string` are used for the more specific integer, number and string
+/// types that don't have their own entry. `{inner}`, `{value}`, `{name}` are replaced by the item
+/// type of lists / sets / nullable types, the value type of maps, and the referenced schema name
+/// or string constant.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LanguageTypeMapping {
+    bool: Option<String>,
+    integer: Option<String>,
+    int16: Option<String>,
+    uint16: Option<String>,
+    int32: Option<String>,
+    int64: Option<String>,
+    uint64: Option<String>,
+    number: Option<String>,
+    float32: Option<String>,
+    float64: Option<String>,
+    decimal: Option<String>,
+    string: Option<String>,
+    date_time: Option<String>,
+    date: Option<String>,
+    time: Option<String>,
+    duration: Option<String>,
+    uri: Option<String>,
+    uuid: Option<String>,
+    email: Option<String>,
+    ipv4: Option<String>,
+    ipv6: Option<String>,
+    base64: Option<String>,
+    binary: Option<String>,
+    json_object: Option<String>,
+    list: Option<String>,
+    set: Option<String>,
+    map: Option<String>,
+    schema_ref: Option<String>,
+    string_const: Option<String>,
+    nullable: Option<String>,
+}
+
+impl LanguageTypeMapping {
+    fn typename(&self, ty: &FieldType, lang: &str) -> Result<String, minijinja::Error> {
+        let integer = || self.integer.as_ref();
+        let number = || self.number.as_ref();
+        let string = || self.string.as_ref();
+
+        let (key, pattern) = match ty {
+            FieldType::Bool => ("bool", self.bool.as_ref()),
+            FieldType::Int16 => ("int16", self.int16.as_ref().or_else(integer)),
+            FieldType::UInt16 => ("uint16", self.uint16.as_ref().or_else(integer)),
+            FieldType::Int32 => ("int32", self.int32.as_ref().or_else(integer)),
+            FieldType::Int64 => ("int64", self.int64.as_ref().or_else(integer)),
+            FieldType::UInt64 => ("uint64", self.uint64.as_ref().or_else(integer)),
+            FieldType::Float32 => ("float32", self.float32.as_ref().or_else(number)),
+            FieldType::Float64 => ("float64", self.float64.as_ref().or_else(number)),
+            FieldType::Decimal => ("decimal", self.decimal.as_ref().or_else(number)),
+            FieldType::String => ("string", self.string.as_ref()),
+            FieldType::DateTime => ("date_time", self.date_time.as_ref().or_else(string)),
+            FieldType::Date => ("date", self.date.as_ref().or_else(string)),
+            FieldType::Time => ("time", self.time.as_ref().or_else(string)),
+            FieldType::Duration => ("duration", self.duration.as_ref().or_else(string)),
+            FieldType::Uri => ("uri", self.uri.as_ref().or_else(string)),
+            FieldType::Uuid => ("uuid", self.uuid.as_ref().or_else(string)),
+            FieldType::Email => ("email", self.email.as_ref().or_else(string)),
+            FieldType::Ipv4 => ("ipv4", self.ipv4.as_ref().or_else(string)),
+            FieldType::Ipv6 => ("ipv6", self.ipv6.as_ref().or_else(string)),
+            FieldType::Base64 => ("base64", self.base64.as_ref().or_else(string)),
+            FieldType::Binary => ("binary", self.binary.as_ref()),
+            FieldType::JsonObject => ("json_object", self.json_object.as_ref()),
+            FieldType::List { .. } => ("list", self.list.as_ref()),
+            FieldType::Set { .. } => ("set", self.set.as_ref()),
+            FieldType::Map { .. } => ("map", self.map.as_ref()),
+            FieldType::SchemaRef { .. } => ("schema_ref", self.schema_ref.as_ref()),
+            FieldType::StringConst { .. } => {
+                ("string_const", self.string_const.as_ref().or_else(string))
+            }
+            FieldType::Custom {
+                base, typenames, ..
+            } => {
+                return match typenames.get(lang) {
+                    Some(typename) => Ok(typename.clone()),
+                    None => self.typename(base, lang),
+                };
+            }
+        };
+
+        let pattern = pattern.ok_or_else(|| missing_mapping(key, lang))?;
+        Ok(match ty {
+            FieldType::List { inner } | FieldType::Set { inner } => {
+                pattern.replace("{inner}", &self.typename(inner, lang)?)
+            }
+            FieldType::Map { value_ty } => {
+                pattern.replace("{value}", &self.typename(value_ty, lang)?)
+            }
+            FieldType::SchemaRef { name } => pattern.replace("{name}", name),
+            FieldType::StringConst { value } => pattern.replace("{name}", value),
+            _ => pattern.clone(),
+        })
+    }
+}
+
+/// Get the type name of `ty` in language `lang`, from the type mappings in the template globals.
+pub(crate) fn to_lang(
+    state: &State<'_, '_>,
+    ty: &FieldType,
+    lang: &str,
+    nullable: bool,
+) -> Result<String, minijinja::Error> {
+    let mappings = state.lookup(TYPE_MAPPINGS_GLOBAL).ok_or_else(|| {
+        minijinja::Error::new(
+            ErrorKind::InvalidOperation,
+            "to_lang requires a types.toml next to the templates",
+        )
+    })?;
+    let mappings = mappings
+        .downcast_object_ref::<TypeMappings>()
+        .expect("type mappings global must be a TypeMappings object");
+    let mapping = mappings.0.get(lang).ok_or_else(|| {
+        minijinja::Error::new(
+            ErrorKind::InvalidOperation,
+            format!("types.toml has no type mapping for `{lang}`"),
+        )
+    })?;
+
+    let typename = mapping.typename(ty, lang)?;
+    if !nullable {
+        return Ok(typename);
+    }
+
+    let pattern = mapping
+        .nullable
+        .as_ref()
+        .ok_or_else(|| missing_mapping("nullable", lang))?;
+    Ok(pattern.replace("{inner}", &typename))
+}
+
+fn missing_mapping(key: &str, lang: &str) -> minijinja::Error {
+    minijinja::Error::new(
+        ErrorKind::InvalidOperation,
+        format!("types.toml has no `{key}` type mapping for `{lang}`"),
+    )
+}